@@ -0,0 +1,27 @@
+use anchor_lang::{ error::ErrorCode, prelude::*, ZeroCopy };
+use orbit_len::{ Bank, OrbitlenAccount };
+
+/// Reads a zero copy account of the program from its raw data, discriminator included
+pub fn deserialize_zero_copy<T: ZeroCopy>(data: &[u8]) -> Result<T> {
+    let (discriminator, account_data) = data
+        .split_first_chunk::<8>()
+        .ok_or(ErrorCode::AccountDiscriminatorNotFound)?;
+
+    if *discriminator != T::DISCRIMINATOR {
+        return Err(ErrorCode::AccountDiscriminatorMismatch.into());
+    }
+
+    let account_data = account_data
+        .get(..std::mem::size_of::<T>())
+        .ok_or(ErrorCode::AccountDidNotDeserialize)?;
+
+    Ok(bytemuck::pod_read_unaligned(account_data))
+}
+
+pub fn deserialize_bank(data: &[u8]) -> Result<Bank> {
+    deserialize_zero_copy(data)
+}
+
+pub fn deserialize_orbitlen_account(data: &[u8]) -> Result<OrbitlenAccount> {
+    deserialize_zero_copy(data)
+}
@@ -0,0 +1,140 @@
+use std::io;
+
+use anchor_lang::{ AnchorDeserialize, Discriminator };
+use base64::{ engine::general_purpose::STANDARD, Engine };
+use orbit_len::events::*;
+
+const PROGRAM_DATA_LOG_PREFIX: &str = "Program data: ";
+
+macro_rules! orbitlen_events {
+    ($($variant:ident($event:ty)),* $(,)?) => {
+        /// Every event the program emits
+        pub enum OrbitlenEvent {
+            $($variant($event),)*
+        }
+
+        impl OrbitlenEvent {
+            /// Decodes the data of an `emit!`, `None` if the discriminator is not one of the
+            /// program's events
+            pub fn decode(data: &[u8]) -> io::Result<Option<Self>> {
+                let Some((discriminator, mut event_data)) = data.split_first_chunk::<8>() else {
+                    return Ok(None);
+                };
+
+                $(
+                    if *discriminator == <$event>::DISCRIMINATOR {
+                        return Ok(Some(Self::$variant(<$event>::deserialize(&mut event_data)?)));
+                    }
+                )*
+
+                Ok(None)
+            }
+        }
+    };
+}
+
+orbitlen_events! {
+    LendingPoolBankCreate(LendingPoolBankCreateEvent),
+    LendingPoolConfigureEmodeCategory(LendingPoolConfigureEmodeCategoryEvent),
+    BankInterestAccrued(BankInterestAccruedEvent),
+    OrbitlenAccountCreate(OrbitlenAccountCreateEvent),
+    LendingAccountDeposit(LendingAccountDepositEvent),
+    LendingAccountBorrow(LendingAccountBorrowEvent),
+    LendingAccountSetEmode(LendingAccountSetEmodeEvent),
+    LendingAccountLiquidationAuction(LendingAccountLiquidationAuctionEvent),
+    LendingAccountLiquidate(LendingAccountLiquidateEvent),
+    LendingAccountLiquidateWithTokens(LendingAccountLiquidateWithTokensEvent),
+    RaydiumDeposit(RaydiumDepositEvent),
+    RaydiumWithdraw(RaydiumWithdrawEvent),
+    RaydiumSwap(RaydiumSwapEvent),
+    RaydiumCpmmDeposit(RaydiumCpmmDepositEvent),
+}
+
+/// Events the program emitted in a transaction's logs, in order. Data logged while another
+/// program is executing, e.g. inside a Raydium CPI, is skipped.
+pub fn parse_logs<S: AsRef<str>>(logs: &[S]) -> io::Result<Vec<OrbitlenEvent>> {
+    let program_id = orbit_len::ID.to_string();
+    let mut invocations: Vec<&str> = Vec::new();
+    let mut events = Vec::new();
+
+    for log in logs {
+        let log = log.as_ref();
+
+        if let Some(encoded_data) = log.strip_prefix(PROGRAM_DATA_LOG_PREFIX) {
+            if invocations.last() != Some(&program_id.as_str()) {
+                continue;
+            }
+
+            let data = STANDARD.decode(encoded_data).map_err(|error|
+                io::Error::new(io::ErrorKind::InvalidData, error)
+            )?;
+
+            if let Some(event) = OrbitlenEvent::decode(&data)? {
+                events.push(event);
+            }
+        } else if let Some(program_log) = log.strip_prefix("Program ") {
+            let mut words = program_log.split_whitespace();
+
+            match (words.next(), words.next()) {
+                (Some(invoked_program_id), Some("invoke")) => invocations.push(invoked_program_id),
+                (Some(_), Some("success" | "failed:")) => {
+                    invocations.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    Ok(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::{ prelude::Pubkey, Event };
+
+    fn program_data_log(event: &impl Event) -> String {
+        format!("{}{}", PROGRAM_DATA_LOG_PREFIX, STANDARD.encode(event.data()))
+    }
+
+    fn set_emode_event(emode_category: u8) -> LendingAccountSetEmodeEvent {
+        LendingAccountSetEmodeEvent {
+            header: AccountEventHeader {
+                signer: Pubkey::new_unique(),
+                orbitlen_account: Pubkey::new_unique(),
+                orbitlen_account_authority: Pubkey::new_unique(),
+            },
+            emode_category,
+        }
+    }
+
+    #[test]
+    fn parse_logs_skips_data_of_invoked_programs() {
+        let program_id = orbit_len::ID.to_string();
+        let other_program_id = Pubkey::new_unique().to_string();
+
+        let logs = vec![
+            format!("Program {} invoke [1]", program_id),
+            "Program log: Instruction: LendingAccountSetEmode".to_string(),
+            format!("Program {} invoke [2]", other_program_id),
+            program_data_log(&set_emode_event(1)),
+            format!("Program {} success", other_program_id),
+            program_data_log(&set_emode_event(2)),
+            format!("Program {} consumed 5000 of 200000 compute units", program_id),
+            format!("Program {} success", program_id)
+        ];
+
+        let events = parse_logs(&logs).unwrap();
+
+        assert_eq!(events.len(), 1);
+        assert!(
+            matches!(&events[0], OrbitlenEvent::LendingAccountSetEmode(event) if event.emode_category == 2)
+        );
+    }
+
+    #[test]
+    fn decode_ignores_unknown_discriminators() {
+        assert!(OrbitlenEvent::decode(&[0; 16]).unwrap().is_none());
+        assert!(OrbitlenEvent::decode(&[0; 4]).unwrap().is_none());
+    }
+}
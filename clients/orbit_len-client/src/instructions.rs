@@ -0,0 +1,502 @@
+//! Instruction builders. Lending instructions derive their PDAs from the signer and mints, the
+//! Raydium proxies take the program's generated account structs as the pool accounts have to
+//! be looked up from the venue anyway. Remaining accounts are laid out by the helpers in
+//! `remaining_accounts`.
+
+use anchor_lang::{ prelude::*, solana_program::instruction::Instruction, system_program, InstructionData };
+use orbit_len::{ accounts, instruction, BankConfigCompact, EmodeConfigCompact };
+
+use crate::{ pda::*, remaining_accounts::* };
+
+fn build_instruction(
+    accounts: impl ToAccountMetas,
+    data: impl InstructionData,
+    remaining_accounts: &[AccountMeta]
+) -> Instruction {
+    let mut account_metas = accounts.to_account_metas(None);
+    account_metas.extend_from_slice(remaining_accounts);
+
+    Instruction {
+        program_id: orbit_len::ID,
+        accounts: account_metas,
+        data: data.data(),
+    }
+}
+
+/*
+ * Admin instructions
+ */
+
+pub fn lending_pool_add_bank(
+    admin: &Pubkey,
+    bank_mint: &Pubkey,
+    token_program: &Pubkey,
+    bank_config: BankConfigCompact
+) -> Instruction {
+    let bank = find_bank_address(bank_mint).0;
+
+    build_instruction(
+        accounts::LendingPoolAddBank {
+            admin: *admin,
+            program_data: find_program_data_address().0,
+            bank_mint: *bank_mint,
+            bank,
+            liquidity_vault_authority: find_liquidity_vault_authority_address(&bank).0,
+            liquidity_vault: find_liquidity_vault_address(&bank).0,
+            token_program: *token_program,
+            system_program: system_program::ID,
+        },
+        instruction::LendingPoolAddBank { bank_config },
+        &[]
+    )
+}
+
+pub fn initial_vault(admin: &Pubkey, bank_mint: &Pubkey, token_program: &Pubkey) -> Instruction {
+    let bank = find_bank_address(bank_mint).0;
+
+    build_instruction(
+        accounts::InitialVault {
+            admin: *admin,
+            program_data: find_program_data_address().0,
+            bank_mint: *bank_mint,
+            liquidity_vault_authority: find_liquidity_vault_authority_address(&bank).0,
+            liquidity_vault: find_liquidity_vault_address(&bank).0,
+            token_program: *token_program,
+            system_program: system_program::ID,
+        },
+        instruction::InitialVault { bank },
+        &[]
+    )
+}
+
+pub fn lending_pool_configure_emode_category(
+    admin: &Pubkey,
+    category: u8,
+    emode_config: EmodeConfigCompact
+) -> Instruction {
+    build_instruction(
+        accounts::LendingPoolConfigureEmodeCategory {
+            admin: *admin,
+            program_data: find_program_data_address().0,
+            emode_category: find_emode_category_address(category).0,
+            system_program: system_program::ID,
+        },
+        instruction::LendingPoolConfigureEmodeCategory { category, emode_config },
+        &[]
+    )
+}
+
+/*
+ * Permissionless instructions
+ */
+
+pub fn lending_pool_accrue_bank_interest(bank: &Pubkey) -> Instruction {
+    build_instruction(
+        accounts::LendingPoolAccrueBankInterest { bank: *bank },
+        instruction::LendingPoolAccrueBankInterest {},
+        &[]
+    )
+}
+
+pub fn lending_account_update_liquidation_auction(
+    signer: &Pubkey,
+    orbitlen_account: &Pubkey,
+    health_metas: &[AccountMeta]
+) -> Instruction {
+    build_instruction(
+        accounts::LendingAccountUpdateLiquidationAuction {
+            signer: *signer,
+            orbitlen_account: *orbitlen_account,
+        },
+        instruction::LendingAccountUpdateLiquidationAuction {},
+        health_metas
+    )
+}
+
+/// Read-only, simulate it and decode the return data as `LendingAccountHealthReport`
+pub fn lending_account_get_health(
+    orbitlen_account: &Pubkey,
+    health_metas: &[AccountMeta]
+) -> Instruction {
+    build_instruction(
+        accounts::LendingAccountGetHealth { orbitlen_account: *orbitlen_account },
+        instruction::LendingAccountGetHealth {},
+        health_metas
+    )
+}
+
+/*
+ * User instructions
+ */
+
+pub fn initialize_account(authority: &Pubkey) -> Instruction {
+    build_instruction(
+        accounts::OrbitlenAccountInitialize {
+            orbitlen_account: find_orbitlen_account_address(authority).0,
+            authority: *authority,
+            system_program: system_program::ID,
+        },
+        instruction::InitializeAccount {},
+        &[]
+    )
+}
+
+/// `health_metas` are the account's health accounts including the bank while its liquidation
+/// auction is running, see `health_account_metas`, and empty otherwise
+pub fn lending_account_deposit(
+    authority: &Pubkey,
+    bank_mint: &Pubkey,
+    signer_token_account: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+    health_metas: &[AccountMeta],
+    transfer_hook_extra_metas: &[AccountMeta]
+) -> Instruction {
+    let bank = find_bank_address(bank_mint).0;
+
+    build_instruction(
+        accounts::LendingAccountDeposit {
+            orbitlen_account: find_orbitlen_account_address(authority).0,
+            signer: *authority,
+            bank,
+            signer_token_account: *signer_token_account,
+            bank_liquidity_vault: find_liquidity_vault_address(&bank).0,
+            token_program: *token_program,
+            system_program: system_program::ID,
+        },
+        instruction::LendingAccountDeposit { amount },
+        &transfer_account_metas(bank_mint, health_metas, transfer_hook_extra_metas)
+    )
+}
+
+/// `health_metas` are the account's health accounts including the borrowed bank, see
+/// `health_account_metas`
+pub fn lending_account_borrow(
+    authority: &Pubkey,
+    bank_mint: &Pubkey,
+    destination_token_account: &Pubkey,
+    token_program: &Pubkey,
+    amount: u64,
+    health_metas: &[AccountMeta],
+    transfer_hook_extra_metas: &[AccountMeta]
+) -> Instruction {
+    let bank = find_bank_address(bank_mint).0;
+
+    build_instruction(
+        accounts::LendingAccountBorrow {
+            orbitlen_account: find_orbitlen_account_address(authority).0,
+            signer: *authority,
+            bank,
+            destination_token_account: *destination_token_account,
+            bank_liquidity_vault_authority: find_liquidity_vault_authority_address(&bank).0,
+            bank_liquidity_vault: find_liquidity_vault_address(&bank).0,
+            token_program: *token_program,
+        },
+        instruction::LendingAccountBorrow { amount },
+        &transfer_account_metas(bank_mint, health_metas, transfer_hook_extra_metas)
+    )
+}
+
+/// `health_metas` are the account's health accounts under the new `emode_category`, see
+/// `health_account_metas`
+pub fn lending_account_set_emode(
+    authority: &Pubkey,
+    emode_category: u8,
+    health_metas: &[AccountMeta]
+) -> Instruction {
+    build_instruction(
+        accounts::LendingAccountSetEmode {
+            orbitlen_account: find_orbitlen_account_address(authority).0,
+            signer: *authority,
+        },
+        instruction::LendingAccountSetEmode { emode_category },
+        health_metas
+    )
+}
+
+/// `remaining_metas` as laid out by `liquidation_account_metas`
+pub fn lending_account_liquidate(
+    liquidator_authority: &Pubkey,
+    liquidatee_orbitlen_account: &Pubkey,
+    asset_mint: &Pubkey,
+    liab_mint: &Pubkey,
+    asset_amount: u64,
+    remaining_metas: &[AccountMeta]
+) -> Instruction {
+    build_instruction(
+        accounts::LendingAccountLiquidate {
+            asset_bank: find_bank_address(asset_mint).0,
+            liab_bank: find_bank_address(liab_mint).0,
+            liquidator_orbitlen_account: find_orbitlen_account_address(liquidator_authority).0,
+            signer: *liquidator_authority,
+            liquidatee_orbitlen_account: *liquidatee_orbitlen_account,
+        },
+        instruction::LendingAccountLiquidate { asset_amount },
+        remaining_metas
+    )
+}
+
+pub fn lending_account_liquidate_multi(
+    liquidator_authority: &Pubkey,
+    liquidatee_orbitlen_account: &Pubkey,
+    legs: &[LiquidationLeg],
+    liquidatee_health_metas: &[AccountMeta]
+) -> Instruction {
+    build_instruction(
+        accounts::LendingAccountLiquidateMulti {
+            liquidator_orbitlen_account: find_orbitlen_account_address(liquidator_authority).0,
+            signer: *liquidator_authority,
+            liquidatee_orbitlen_account: *liquidatee_orbitlen_account,
+        },
+        instruction::LendingAccountLiquidateMulti {
+            asset_amounts: legs
+                .iter()
+                .map(|leg| leg.asset_amount)
+                .collect(),
+        },
+        &multi_liquidation_account_metas(legs, liquidatee_health_metas)
+    )
+}
+
+/// Mint / token program / liquidator token account of one side of a token settled liquidation
+pub struct LiquidationTokenLeg {
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    pub liquidator_token_account: Pubkey,
+}
+
+/// `remaining_metas` as laid out by `liquidation_account_metas`, followed by any transfer hook
+/// extra accounts
+pub fn lending_account_liquidate_with_tokens(
+    liquidator: &Pubkey,
+    liquidatee_orbitlen_account: &Pubkey,
+    asset: &LiquidationTokenLeg,
+    liab: &LiquidationTokenLeg,
+    asset_amount: u64,
+    remaining_metas: &[AccountMeta]
+) -> Instruction {
+    let asset_bank = find_bank_address(&asset.mint).0;
+    let liab_bank = find_bank_address(&liab.mint).0;
+
+    build_instruction(
+        accounts::LendingAccountLiquidateWithTokens {
+            liquidator: *liquidator,
+            asset_bank,
+            asset_mint: asset.mint,
+            asset_bank_liquidity_vault: find_liquidity_vault_address(&asset_bank).0,
+            asset_bank_liquidity_vault_authority: find_liquidity_vault_authority_address(
+                &asset_bank
+            ).0,
+            liquidator_asset_token_account: asset.liquidator_token_account,
+            asset_token_program: asset.token_program,
+            liab_bank,
+            liab_mint: liab.mint,
+            liab_bank_liquidity_vault: find_liquidity_vault_address(&liab_bank).0,
+            liquidator_liab_token_account: liab.liquidator_token_account,
+            liab_token_program: liab.token_program,
+            liquidatee_orbitlen_account: *liquidatee_orbitlen_account,
+        },
+        instruction::LendingAccountLiquidateWithTokens { asset_amount },
+        remaining_metas
+    )
+}
+
+/*
+ * Raydium AMM v4 proxies, `remaining_metas` start with the account's health accounts
+ */
+
+pub fn raydium_deposit(
+    accounts: accounts::ProxyDeposit,
+    coin_amount: u64,
+    pc_amount: u64,
+    min_lp_amount: u64,
+    remaining_metas: &[AccountMeta]
+) -> Instruction {
+    build_instruction(
+        accounts,
+        instruction::RaydiumDeposit { coin_amount, pc_amount, min_lp_amount },
+        remaining_metas
+    )
+}
+
+pub fn raydium_withdraw(
+    accounts: accounts::ProxyWithdraw,
+    amount: u64,
+    min_coin_amount: u64,
+    min_pc_amount: u64,
+    remaining_metas: &[AccountMeta]
+) -> Instruction {
+    build_instruction(
+        accounts,
+        instruction::RaydiumWithdraw { amount, min_coin_amount, min_pc_amount },
+        remaining_metas
+    )
+}
+
+pub fn raydium_swap_base_in(
+    accounts: accounts::ProxySwap,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    remaining_metas: &[AccountMeta]
+) -> Instruction {
+    build_instruction(
+        accounts,
+        instruction::RaydiumSwapBaseIn { amount_in, minimum_amount_out },
+        remaining_metas
+    )
+}
+
+pub fn raydium_swap_base_out(
+    accounts: accounts::ProxySwap,
+    max_amount_in: u64,
+    amount_out: u64,
+    remaining_metas: &[AccountMeta]
+) -> Instruction {
+    build_instruction(
+        accounts,
+        instruction::RaydiumSwapBaseOut { max_amount_in, amount_out },
+        remaining_metas
+    )
+}
+
+/*
+ * Raydium CPMM proxies, `remaining_metas` start with the account's health accounts
+ */
+
+pub fn raydium_cpmm_deposit(
+    accounts: accounts::ProxyCpmmDeposit,
+    lp_token_amount: u64,
+    maximum_token_0_amount: u64,
+    maximum_token_1_amount: u64,
+    remaining_metas: &[AccountMeta]
+) -> Instruction {
+    build_instruction(
+        accounts,
+        instruction::RaydiumCpmmDeposit {
+            lp_token_amount,
+            maximum_token_0_amount,
+            maximum_token_1_amount,
+        },
+        remaining_metas
+    )
+}
+
+pub fn raydium_cpmm_withdraw(
+    accounts: accounts::ProxyCpmmWithdraw,
+    lp_token_amount: u64,
+    minimum_token_0_amount: u64,
+    minimum_token_1_amount: u64,
+    remaining_metas: &[AccountMeta]
+) -> Instruction {
+    build_instruction(
+        accounts,
+        instruction::RaydiumCpmmWithdraw {
+            lp_token_amount,
+            minimum_token_0_amount,
+            minimum_token_1_amount,
+        },
+        remaining_metas
+    )
+}
+
+pub fn raydium_cpmm_swap_base_input(
+    accounts: accounts::ProxyCpmmSwap,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    remaining_metas: &[AccountMeta]
+) -> Instruction {
+    build_instruction(
+        accounts,
+        instruction::RaydiumCpmmSwapBaseInput { amount_in, minimum_amount_out },
+        remaining_metas
+    )
+}
+
+pub fn raydium_cpmm_swap_base_output(
+    accounts: accounts::ProxyCpmmSwap,
+    max_amount_in: u64,
+    amount_out: u64,
+    remaining_metas: &[AccountMeta]
+) -> Instruction {
+    build_instruction(
+        accounts,
+        instruction::RaydiumCpmmSwapBaseOutput { max_amount_in, amount_out },
+        remaining_metas
+    )
+}
+
+/*
+ * Raydium CLMM proxies, `remaining_metas` start with the account's health accounts
+ */
+
+/// Tick range of a new CLMM position and the tick arrays holding its bounds
+pub struct ClmmTickRange {
+    pub tick_lower_index: i32,
+    pub tick_upper_index: i32,
+    pub tick_array_lower_start_index: i32,
+    pub tick_array_upper_start_index: i32,
+}
+
+pub fn raydium_clmm_open_position(
+    accounts: accounts::ProxyClmmOpenPosition,
+    tick_range: ClmmTickRange,
+    liquidity: u128,
+    amount_0_max: u64,
+    amount_1_max: u64,
+    remaining_metas: &[AccountMeta]
+) -> Instruction {
+    build_instruction(
+        accounts,
+        instruction::RaydiumClmmOpenPosition {
+            tick_lower_index: tick_range.tick_lower_index,
+            tick_upper_index: tick_range.tick_upper_index,
+            tick_array_lower_start_index: tick_range.tick_array_lower_start_index,
+            tick_array_upper_start_index: tick_range.tick_array_upper_start_index,
+            liquidity,
+            amount_0_max,
+            amount_1_max,
+        },
+        remaining_metas
+    )
+}
+
+pub fn raydium_clmm_increase_liquidity(
+    accounts: accounts::ProxyClmmIncreaseLiquidity,
+    liquidity: u128,
+    amount_0_max: u64,
+    amount_1_max: u64,
+    remaining_metas: &[AccountMeta]
+) -> Instruction {
+    build_instruction(
+        accounts,
+        instruction::RaydiumClmmIncreaseLiquidity { liquidity, amount_0_max, amount_1_max },
+        remaining_metas
+    )
+}
+
+/// Reward accounts of the pool follow the health accounts in `remaining_metas`
+pub fn raydium_clmm_decrease_liquidity(
+    accounts: accounts::ProxyClmmDecreaseLiquidity,
+    liquidity: u128,
+    amount_0_min: u64,
+    amount_1_min: u64,
+    remaining_metas: &[AccountMeta]
+) -> Instruction {
+    build_instruction(
+        accounts,
+        instruction::RaydiumClmmDecreaseLiquidity { liquidity, amount_0_min, amount_1_min },
+        remaining_metas
+    )
+}
+
+/// Reward accounts of the pool follow the health accounts in `remaining_metas`
+pub fn raydium_clmm_collect_fees(
+    accounts: accounts::ProxyClmmDecreaseLiquidity,
+    remaining_metas: &[AccountMeta]
+) -> Instruction {
+    build_instruction(accounts, instruction::RaydiumClmmCollectFees {}, remaining_metas)
+}
+
+pub fn raydium_clmm_close_position(accounts: accounts::ProxyClmmClosePosition) -> Instruction {
+    build_instruction(accounts, instruction::RaydiumClmmClosePosition {}, &[])
+}
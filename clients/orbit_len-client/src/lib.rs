@@ -0,0 +1,14 @@
+//! Client for the orbit_len program: PDA derivation, instruction builders with their remaining
+//! account layouts, account deserialization, event decoding from transaction logs and health
+//! simulations on top of the program's own math.
+
+pub mod accounts;
+pub mod events;
+pub mod instructions;
+pub mod pda;
+pub mod remaining_accounts;
+pub mod simulator;
+
+pub use orbit_len_math as math;
+
+pub use orbit_len::ID as PROGRAM_ID;
@@ -0,0 +1,41 @@
+use anchor_lang::{ prelude::Pubkey, solana_program::bpf_loader_upgradeable };
+use orbit_len::constants::*;
+
+pub fn find_bank_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BANK_SEED.as_bytes(), mint.as_ref()], &orbit_len::ID)
+}
+
+pub fn find_liquidity_vault_address(bank: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LIQUIDITY_VAULT_SEED.as_bytes(), bank.as_ref()], &orbit_len::ID)
+}
+
+pub fn find_liquidity_vault_authority_address(bank: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[LIQUIDITY_VAULT_AUTHORITY_SEED.as_bytes(), bank.as_ref()],
+        &orbit_len::ID
+    )
+}
+
+pub fn find_orbitlen_account_address(authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[ORBITLEN_ACCOUNT_SEED.as_bytes(), authority.as_ref()],
+        &orbit_len::ID
+    )
+}
+
+pub fn find_emode_category_address(category: u8) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EMODE_CATEGORY_SEED.as_bytes(), &[category]], &orbit_len::ID)
+}
+
+/// Holder of the CLMM position nfts of `orbitlen_account`
+pub fn find_clmm_position_authority_address(orbitlen_account: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &[CLMM_POSITION_AUTHORITY_SEED.as_bytes(), orbitlen_account.as_ref()],
+        &orbit_len::ID
+    )
+}
+
+/// Program data account of the program, its upgrade authority is the admin
+pub fn find_program_data_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[orbit_len::ID.as_ref()], &bpf_loader_upgradeable::ID)
+}
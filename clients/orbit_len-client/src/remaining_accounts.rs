@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+
+use anchor_lang::prelude::*;
+use orbit_len::{ error::OrbitlenError, Bank, BankType, OrbitlenAccount, EMODE_CATEGORY_NONE };
+
+use crate::pda::find_emode_category_address;
+
+/// Oracle accounts `fetch_bank_price` expects for `bank`
+pub fn oracle_account_metas(bank: &Bank) -> Vec<AccountMeta> {
+    let oracle_keys = match bank.config.get_bank_type() {
+        Some(BankType::RaydiumLp) => {
+            let lp_config = bank.config.raydium_lp_config;
+
+            vec![
+                lp_config.coin_feed_data_key,
+                lp_config.pc_feed_data_key,
+                lp_config.amm,
+                lp_config.amm_open_orders,
+                lp_config.amm_coin_vault,
+                lp_config.amm_pc_vault,
+                bank.mint
+            ]
+        }
+        Some(BankType::RaydiumCpmmLp) => {
+            let lp_config = bank.config.raydium_cpmm_lp_config;
+
+            vec![
+                lp_config.token_0_feed_data_key,
+                lp_config.token_1_feed_data_key,
+                lp_config.pool_state,
+                lp_config.token_0_vault,
+                lp_config.token_1_vault
+            ]
+        }
+        _ => vec![bank.config.feed_data_key],
+    };
+
+    oracle_keys
+        .into_iter()
+        .map(|key| AccountMeta::new_readonly(key, false))
+        .collect()
+}
+
+/// A bank followed by its oracle accounts
+pub fn bank_account_metas(bank_pk: &Pubkey, bank: &Bank, is_writable: bool) -> Vec<AccountMeta> {
+    let bank_meta = if is_writable {
+        AccountMeta::new(*bank_pk, false)
+    } else {
+        AccountMeta::new_readonly(*bank_pk, false)
+    };
+
+    std::iter::once(bank_meta).chain(oracle_account_metas(bank)).collect()
+}
+
+/// Health accounts of `orbitlen_account`, see `RiskEngine::calc_account_health`: the e-mode
+/// category account when the account opted into one, then one bank / oracle group per active
+/// balance, plus `extra_banks` the instruction opens a balance in. `banks` has to hold every
+/// one of those banks.
+pub fn health_account_metas(
+    orbitlen_account: &OrbitlenAccount,
+    banks: &HashMap<Pubkey, Bank>,
+    extra_banks: &[Pubkey]
+) -> Result<Vec<AccountMeta>> {
+    let mut bank_pks: Vec<Pubkey> = orbitlen_account.lending_account.balances
+        .iter()
+        .map(|balance| balance.bank_pk)
+        .filter(|bank_pk| *bank_pk != Pubkey::default())
+        .collect();
+
+    for bank_pk in extra_banks {
+        if !bank_pks.contains(bank_pk) {
+            bank_pks.push(*bank_pk);
+        }
+    }
+
+    let mut account_metas = Vec::new();
+    if orbitlen_account.emode_category != EMODE_CATEGORY_NONE {
+        account_metas.push(
+            AccountMeta::new_readonly(
+                find_emode_category_address(orbitlen_account.emode_category).0,
+                false
+            )
+        );
+    }
+    for bank_pk in bank_pks {
+        let bank = banks.get(&bank_pk).ok_or(OrbitlenError::InvalidHealthAccounts)?;
+        account_metas.extend(bank_account_metas(&bank_pk, bank, false));
+    }
+
+    Ok(account_metas)
+}
+
+/// Remaining accounts of deposits and borrows: the bank mint, any health accounts with the bank
+/// among the `extra_banks` of `health_account_metas`, then any transfer hook extra accounts
+pub fn transfer_account_metas(
+    mint: &Pubkey,
+    health_metas: &[AccountMeta],
+    transfer_hook_extra_metas: &[AccountMeta]
+) -> Vec<AccountMeta> {
+    std::iter
+        ::once(AccountMeta::new_readonly(*mint, false))
+        .chain(health_metas.iter().cloned())
+        .chain(transfer_hook_extra_metas.iter().cloned())
+        .collect()
+}
+
+/// Remaining accounts of single pair liquidations: the asset and liab bank oracle accounts,
+/// then the liquidatee's health accounts
+pub fn liquidation_account_metas(
+    asset_bank: &Bank,
+    liab_bank: &Bank,
+    liquidatee_health_metas: &[AccountMeta]
+) -> Vec<AccountMeta> {
+    oracle_account_metas(asset_bank)
+        .into_iter()
+        .chain(oracle_account_metas(liab_bank))
+        .chain(liquidatee_health_metas.iter().cloned())
+        .collect()
+}
+
+/// Asset / liab bank pair of `lending_account_liquidate_multi`
+pub struct LiquidationLeg<'a> {
+    pub asset_bank_pk: Pubkey,
+    pub asset_bank: &'a Bank,
+    pub liab_bank_pk: Pubkey,
+    pub liab_bank: &'a Bank,
+    /// Collateral seized from the asset bank
+    pub asset_amount: u64,
+}
+
+/// Remaining accounts of multi-leg liquidations: the writable bank / oracle groups of every
+/// leg, then the liquidatee's health accounts
+pub fn multi_liquidation_account_metas(
+    legs: &[LiquidationLeg],
+    liquidatee_health_metas: &[AccountMeta]
+) -> Vec<AccountMeta> {
+    legs.iter()
+        .flat_map(|leg| {
+            bank_account_metas(&leg.asset_bank_pk, leg.asset_bank, true)
+                .into_iter()
+                .chain(bank_account_metas(&leg.liab_bank_pk, leg.liab_bank, true))
+        })
+        .chain(liquidatee_health_metas.iter().cloned())
+        .collect()
+}
@@ -0,0 +1,251 @@
+//! Projects an account's health along a path of timestamps and prices. Copies of the banks are
+//! accrued with the program's own `Bank::apply_interest_accrual` and the balances are valued
+//! and weighed with `RiskEngine`, so every step matches what the program would compute at that
+//! time and prices. The banks see no other flows, utilization only moves with accrued interest.
+
+use std::collections::HashMap;
+
+use anchor_lang::prelude::*;
+use orbit_len::{
+    error::OrbitlenError,
+    AccountHealth,
+    BalanceValue,
+    Bank,
+    BankInterestAccruedEvent,
+    EmodeConfig,
+    OrbitlenAccount,
+    RiskEngine,
+    RiskRequirementType,
+};
+
+/// Timestamp and price per whole token of each bank, keyed by bank, along a simulated path
+pub struct PathPoint {
+    pub timestamp: i64,
+    pub prices: HashMap<Pubkey, f64>,
+}
+
+pub struct HealthProjection {
+    pub timestamp: i64,
+    /// Active balances in account order, the order `health_account_metas` lays them out in
+    pub balances: Vec<BalanceValue>,
+    pub initial: AccountHealth,
+    pub maintenance: AccountHealth,
+    /// Interest accrued into the account's banks since the previous step
+    pub accruals: Vec<BankInterestAccruedEvent>,
+}
+
+impl HealthProjection {
+    pub fn is_liquidatable(&self) -> bool {
+        !self.maintenance.is_healthy()
+    }
+}
+
+pub struct HealthSimulator {
+    orbitlen_account: OrbitlenAccount,
+    banks: HashMap<Pubkey, Bank>,
+    emode_config: Option<EmodeConfig>,
+}
+
+impl HealthSimulator {
+    /// `banks` has to hold every bank the account has a balance in, `emode_config` are the
+    /// weights of the e-mode category the account opted into, if any
+    pub fn new(
+        orbitlen_account: OrbitlenAccount,
+        banks: HashMap<Pubkey, Bank>,
+        emode_config: Option<EmodeConfig>
+    ) -> Result<Self> {
+        let simulator = Self { orbitlen_account, banks, emode_config };
+
+        for bank_pk in simulator.active_bank_pks() {
+            require!(simulator.banks.contains_key(&bank_pk), OrbitlenError::InvalidHealthAccounts);
+        }
+
+        Ok(simulator)
+    }
+
+    pub fn bank(&self, bank_pk: &Pubkey) -> Option<&Bank> {
+        self.banks.get(bank_pk)
+    }
+
+    /// Accrues the account's banks up to `timestamp` and values its balances at `prices`
+    pub fn step(
+        &mut self,
+        timestamp: i64,
+        prices: &HashMap<Pubkey, f64>
+    ) -> Result<HealthProjection> {
+        let mut balance_values = Vec::new();
+        let mut accruals = Vec::new();
+
+        for balance in self.orbitlen_account.lending_account.balances.iter() {
+            if balance.bank_pk == Pubkey::default() {
+                continue;
+            }
+
+            let bank = self.banks
+                .get_mut(&balance.bank_pk)
+                .ok_or(OrbitlenError::InvalidHealthAccounts)?;
+
+            // The program can't accrue backwards either
+            require_gte!(timestamp, bank.last_update, OrbitlenError::MathError);
+            if let Some(accrual) = bank.apply_interest_accrual(timestamp, &balance.bank_pk)? {
+                accruals.push(accrual);
+            }
+
+            let price = *prices.get(&balance.bank_pk).ok_or(OrbitlenError::FetchPriceFailed)?;
+
+            balance_values.push(
+                RiskEngine::calc_balance_value(&balance.bank_pk, bank, balance, price)?
+            );
+        }
+
+        Ok(HealthProjection {
+            timestamp,
+            initial: RiskEngine::weigh_balance_values(
+                &self.orbitlen_account,
+                self.emode_config.as_ref(),
+                &balance_values,
+                RiskRequirementType::Initial
+            ),
+            maintenance: RiskEngine::weigh_balance_values(
+                &self.orbitlen_account,
+                self.emode_config.as_ref(),
+                &balance_values,
+                RiskRequirementType::Maintenance
+            ),
+            balances: balance_values,
+            accruals,
+        })
+    }
+
+    /// Steps through `path` in order, timestamps must not decrease
+    pub fn simulate(&mut self, path: &[PathPoint]) -> Result<Vec<HealthProjection>> {
+        path.iter()
+            .map(|point| self.step(point.timestamp, &point.prices))
+            .collect()
+    }
+
+    fn active_bank_pks(&self) -> impl Iterator<Item = Pubkey> + '_ {
+        self.orbitlen_account.lending_account.balances
+            .iter()
+            .map(|balance| balance.bank_pk)
+            .filter(|bank_pk| *bank_pk != Pubkey::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+    use orbit_len::{ BankConfig, InterestRateConfig, SECONDS_PER_YEAR };
+
+    const START_TIMESTAMP: i64 = 1_700_000_000;
+
+    fn bank(mint_decimals: u8) -> Bank {
+        let config = BankConfig {
+            interest_rate_config: InterestRateConfig {
+                optimal_utilization_rate: 80,
+                plateau_interest_rate: 10,
+                max_interest_rate: 100,
+                ..Default::default()
+            },
+            asset_weight_init_bps: 8_000,
+            asset_weight_maint_bps: 9_000,
+            liability_weight_init_bps: 12_000,
+            liability_weight_maint_bps: 11_000,
+            close_factor_bps: 5_000,
+            ..Default::default()
+        };
+
+        Bank::new(
+            Pubkey::new_unique(),
+            mint_decimals,
+            config,
+            START_TIMESTAMP,
+            Pubkey::default(),
+            0,
+            0
+        )
+    }
+
+    /// Borrows 500 SOL-like tokens against 100k USD of collateral, half of the borrowing bank
+    /// being lent out
+    fn simulator() -> (HealthSimulator, Pubkey, Pubkey) {
+        simulator_with_sol_share_value(1)
+    }
+
+    /// `simulator` with the borrowing bank's share values at `sol_share_value`, share counts
+    /// are scaled down so the amounts stay the same
+    fn simulator_with_sol_share_value(sol_share_value: u64) -> (HealthSimulator, Pubkey, Pubkey) {
+        let (usdc_bank_pk, sol_bank_pk) = (Pubkey::new_unique(), Pubkey::new_unique());
+
+        let mut usdc_bank = bank(6);
+        usdc_bank.total_asset_shares = 100_000_000_000;
+
+        let mut sol_bank = bank(9);
+        sol_bank.asset_share_value = sol_share_value;
+        sol_bank.liability_share_value = sol_share_value;
+        sol_bank.total_asset_shares = 1_000_000_000_000 / sol_share_value;
+        sol_bank.total_liability_shares = 500_000_000_000 / sol_share_value;
+
+        let mut orbitlen_account = OrbitlenAccount::zeroed();
+        let balances = &mut orbitlen_account.lending_account.balances;
+        balances[0].bank_pk = usdc_bank_pk;
+        balances[0].asset_shares = 100_000_000_000;
+        balances[1].bank_pk = sol_bank_pk;
+        balances[1].liability_shares = 500_000_000_000 / sol_share_value;
+
+        let banks = HashMap::from([
+            (usdc_bank_pk, usdc_bank),
+            (sol_bank_pk, sol_bank),
+        ]);
+
+        (HealthSimulator::new(orbitlen_account, banks, None).unwrap(), usdc_bank_pk, sol_bank_pk)
+    }
+
+    #[test]
+    fn step_accrues_a_year_of_interest() {
+        // Share values of 1 can't move, f32 accrual truncates them back to 1
+        let (mut simulator, usdc_bank_pk, sol_bank_pk) = simulator_with_sol_share_value(1_000_000);
+        let timestamp = START_TIMESTAMP + (SECONDS_PER_YEAR as i64);
+
+        let prices = HashMap::from([
+            (usdc_bank_pk, 1.0),
+            (sol_bank_pk, 100.0),
+        ]);
+        let projection = simulator.step(timestamp, &prices).unwrap();
+
+        // 50% utilization on the linear curve: 10% * 50 / 80 borrowing, half of it lending
+        let sol_bank = *simulator.bank(&sol_bank_pk).unwrap();
+        assert_eq!({ sol_bank.liability_share_value }, 1_062_500);
+        assert_eq!({ sol_bank.asset_share_value }, 1_031_250);
+        assert_eq!({ sol_bank.last_update }, timestamp);
+        assert_eq!(projection.accruals.len(), 2);
+        assert_eq!(projection.balances[1].liability_amount, 531_250_000_000);
+    }
+
+    #[test]
+    fn price_path_turns_account_liquidatable() {
+        let (mut simulator, usdc_bank_pk, sol_bank_pk) = simulator();
+
+        let path: Vec<PathPoint> = [100.0, 150.0, 200.0]
+            .into_iter()
+            .enumerate()
+            .map(|(day, sol_price)| PathPoint {
+                timestamp: START_TIMESTAMP + (day as i64) * 86_400,
+                prices: HashMap::from([
+                    (usdc_bank_pk, 1.0),
+                    (sol_bank_pk, sol_price),
+                ]),
+            })
+            .collect();
+
+        let projections = simulator.simulate(&path).unwrap();
+
+        // 90k weighted collateral against 55k, 82.5k and 110k weighted debt
+        assert!(projections[0].initial.is_healthy());
+        assert!(!projections[1].initial.is_healthy());
+        assert!(!projections[1].is_liquidatable());
+        assert!(projections[2].is_liquidatable());
+        assert!(simulator.step(START_TIMESTAMP, &path[0].prices).is_err());
+    }
+}
@@ -0,0 +1,410 @@
+//! Pure interest, share, price and liquidation math behind `Bank`, `InterestRateConfig`, the
+//! price fetchers and the risk engine.
+//!
+//! Takes no accounts, only plain integers and floats, so host code (see `orbit_len-client`'s
+//! simulator) runs the same functions as the program and gets the same results. Keep the float
+//! operations and their order exactly as the program executes them, any rewrite changes the
+//! results.
+//!
+//! `no_std`, only `core` float operations are used, so the crate builds for the program, host
+//! code and any other target alike.
+
+#![no_std]
+
+pub const SECONDS_PER_YEAR: usize = 31_536_000;
+
+pub const BASIS_POINTS: u32 = 10_000;
+
+pub const ADAPTIVE_RATE_CURVE_STEEPNESS: f32 = 4.0;
+
+// Shares
+
+pub fn shares_to_amount(shares: u64, share_value: u64) -> Option<u64> {
+    shares.checked_mul(share_value)
+}
+
+pub fn amount_to_shares(amount: u64, share_value: u64) -> Option<u64> {
+    amount.checked_div(share_value)
+}
+
+/// Utilization the interest rate curves are evaluated at, 0 for an empty bank
+pub fn utilization_ratio(total_assets: u64, total_liabilities: u64) -> f32 {
+    if total_assets == 0 {
+        return 0.0;
+    }
+
+    (total_liabilities as f32) / (total_assets as f32)
+}
+
+/// Utilization `accrue_interest` prices the elapsed period at, divided in double precision
+pub fn accrual_utilization_ratio(total_assets: u64, total_liabilities: u64) -> f32 {
+    ((total_liabilities as f64) / (total_assets as f64)) as f32
+}
+
+/// Share value after `time_delta` seconds at `apr`, simple interest over the period
+pub fn accrue_share_value(share_value: u64, apr: f32, time_delta: u64) -> u64 {
+    let ir_per_period = (apr * (time_delta as f32)) / (SECONDS_PER_YEAR as f32);
+
+    ((share_value as f32) * (1.0 + ir_per_period)) as u64
+}
+
+// Interest rate curves
+
+pub fn hundredths_as_float(value: u16) -> f32 {
+    (value as f32) / 100.0
+}
+
+pub fn bps_as_float(value: u32) -> f32 {
+    (value as f32) / (BASIS_POINTS as f32)
+}
+
+/// `(lending_apr, borrowing_apr)` for the curve's borrowing rate at `utilization_ratio`
+pub fn interest_rates(base_rate: f32, utilization_ratio: f32) -> (f32, f32) {
+    (base_rate * utilization_ratio, base_rate)
+}
+
+pub fn linear_interest_rate(ur: f32, optimal_ur: f32, plateau_ir: f32, max_ir: f32) -> f32 {
+    if ur <= optimal_ur {
+        (ur / optimal_ur) * plateau_ir
+    } else {
+        ((ur - optimal_ur) / (1.0 - optimal_ur)) * (max_ir - plateau_ir) + plateau_ir
+    }
+}
+
+/// Piecewise linear curve through `(0, base_ir)`, every `(utilization, rate)` kink and
+/// `(1, max_ir)`. Kinks must be strictly increasing.
+pub fn multi_kink_interest_rate(
+    ur: f32,
+    base_ir: f32,
+    kinks: impl IntoIterator<Item = (f32, f32)>,
+    max_ir: f32
+) -> f32 {
+    let points = kinks.into_iter().chain(core::iter::once((1.0, max_ir)));
+
+    let (mut prev_ur, mut prev_ir) = (0.0, base_ir);
+
+    for (kink_ur, kink_ir) in points {
+        if ur <= kink_ur {
+            return prev_ir + ((ur - prev_ur) / (kink_ur - prev_ur)) * (kink_ir - prev_ir);
+        }
+        (prev_ur, prev_ir) = (kink_ur, kink_ir);
+    }
+
+    prev_ir
+}
+
+/// Curve pivoting around `rate_at_target` at `optimal_ur`, running from
+/// `rate_at_target / ADAPTIVE_RATE_CURVE_STEEPNESS` at 0% to
+/// `rate_at_target * ADAPTIVE_RATE_CURVE_STEEPNESS` at 100%.
+pub fn adaptive_interest_rate(ur: f32, optimal_ur: f32, rate_at_target: f32) -> f32 {
+    let min_ir = rate_at_target / ADAPTIVE_RATE_CURVE_STEEPNESS;
+    let max_ir = rate_at_target * ADAPTIVE_RATE_CURVE_STEEPNESS;
+    if ur <= optimal_ur {
+        min_ir + (ur / optimal_ur) * (rate_at_target - min_ir)
+    } else {
+        rate_at_target +
+            ((ur.min(1.0) - optimal_ur) / (1.0 - optimal_ur)) * (max_ir - rate_at_target)
+    }
+}
+
+/// Distance of the utilization from `optimal_ur`, scaled to [-1, 1] on both sides of it
+pub fn utilization_error(ur: f32, optimal_ur: f32) -> f32 {
+    let ur = ur.clamp(0.0, 1.0);
+    if ur > optimal_ur {
+        (ur - optimal_ur) / (1.0 - optimal_ur)
+    } else {
+        (ur - optimal_ur) / optimal_ur
+    }
+}
+
+/// Velocity-form PI step of the adaptive curve's rate at target, before clamping
+pub fn adaptive_rate_at_target(
+    rate_at_target: f32,
+    last_error: f32,
+    error: f32,
+    kp: f32,
+    ki: f32,
+    time_delta: u64
+) -> f32 {
+    let time_delta_years = (time_delta as f32) / (SECONDS_PER_YEAR as f32);
+
+    rate_at_target + kp * (error - last_error) + ki * error * time_delta_years
+}
+
+// Prices and values
+
+pub fn as_ui_amount(amount: u64, mint_decimals: u8) -> f64 {
+    (amount as f64) / (10u64.pow(mint_decimals as u32) as f64)
+}
+
+/// Value of `amount` native tokens at `price` per whole token, in feed currency
+pub fn token_value(amount: u64, mint_decimals: u8, price: f64) -> f64 {
+    as_ui_amount(amount, mint_decimals) * price
+}
+
+/// Fair value of one LP token of a constant product pool, `2 * sqrt(coin_value * pc_value)`
+/// over the LP supply, 0 while no LP token is outstanding. That is the pool value at the
+/// reserves the feed prices imply, so a swap skewing the reserves along `x * y = k` doesn't
+/// change it and a donation only adds what it donates, unlike the sum of the reserve values.
+pub fn lp_token_price(coin_value: f64, pc_value: f64, lp_supply: u64, lp_mint_decimals: u8) -> f64 {
+    if lp_supply == 0 {
+        return 0.0;
+    }
+
+    (2.0 * sqrt(coin_value * pc_value)) / as_ui_amount(lp_supply, lp_mint_decimals)
+}
+
+/// Square root of a non-negative `x`, rounded to nearest like `f64::sqrt`, which needs `std`.
+/// NaN for a negative or NaN `x`.
+pub fn sqrt(x: f64) -> f64 {
+    if x.is_nan() || x < 0.0 {
+        return f64::NAN;
+    }
+    if x == 0.0 || x.is_infinite() {
+        return x;
+    }
+
+    // x = mantissa * 2^exponent, with the mantissa normalized to 53 bits
+    let bits = x.to_bits();
+    let (mut mantissa, mut exponent) = match (bits >> 52) as i32 {
+        0 => (bits, -1074),
+        biased_exponent => ((bits & ((1 << 52) - 1)) | (1 << 52), biased_exponent - 1075),
+    };
+    while mantissa < 1 << 52 {
+        mantissa <<= 1;
+        exponent -= 1;
+    }
+
+    // sqrt(x) = sqrt(n) * 2^((exponent - shift) / 2), the shift making the exponent even and
+    // the integer root of n 53 bits long
+    let shift = if exponent % 2 == 0 { 52 } else { 53 };
+    let n = (mantissa as u128) << shift;
+
+    let mut root = approx_sqrt(n as f64) as u128;
+    while root * root > n {
+        root -= 1;
+    }
+    while (root + 1) * (root + 1) <= n {
+        root += 1;
+    }
+    // n is an integer, so its root is never halfway between two integers
+    if n - root * root > root {
+        root += 1;
+    }
+
+    let scale = f64::from_bits((((exponent - shift) / 2 + 1023) as u64) << 52);
+
+    (root as f64) * scale
+}
+
+/// Newton's method from the estimate halving the exponent, within an ulp or so of the root of
+/// a positive normal `x`
+fn approx_sqrt(x: f64) -> f64 {
+    let estimate = f64::from_bits((x.to_bits() >> 1) + ((1023u64 << 52) >> 1));
+    let mut root = 0.5 * (estimate + x / estimate);
+
+    loop {
+        let next = 0.5 * (root + x / root);
+        if next >= root {
+            return root;
+        }
+        root = next;
+    }
+}
+
+/// Native tokens worth `value` in feed currency at `price` per whole token, rounded down.
+/// `None` for a non-positive price or an amount that doesn't fit a `u64`.
+pub fn token_amount(value: f64, mint_decimals: u8, price: f64) -> Option<u64> {
+    if price <= 0.0 || price.is_nan() {
+        return None;
+    }
+
+    let amount = (value / price) * (10u64.pow(mint_decimals as u32) as f64);
+
+    // the cast truncates, which rounds non-negative amounts down
+    if (0.0..(u64::MAX as f64)).contains(&amount) { Some(amount as u64) } else { None }
+}
+
+// Risk
+
+pub fn bps_as_weight(weight_bps: u16) -> f64 {
+    (weight_bps as f64) / (BASIS_POINTS as f64)
+}
+
+/// Whether `assets` cover `liabilities` by more than `buffer_bps` of the liabilities
+pub fn exceeds_buffer(assets: f64, liabilities: f64, buffer_bps: u32) -> bool {
+    assets > liabilities * (1.0 + (buffer_bps as f64) / (BASIS_POINTS as f64))
+}
+
+// Liquidations
+
+/// Bonus of an auction `elapsed_slots` in, rising linearly to `max_bonus_bps` over
+/// `auction_slots`
+pub fn auction_liquidation_bonus_bps(
+    max_bonus_bps: u32,
+    auction_slots: u64,
+    elapsed_slots: u64
+) -> u32 {
+    (((max_bonus_bps as u64) * elapsed_slots.min(auction_slots)) / auction_slots) as u32
+}
+
+/// Liability a liquidator repays for `asset_amount` of collateral, the collateral being worth
+/// the liability plus `bonus_bps` of it
+pub fn liquidation_liab_amount(
+    asset_amount: u64,
+    asset_price: f64,
+    asset_mint_decimals: u8,
+    liab_price: f64,
+    liab_mint_decimals: u8,
+    bonus_bps: u32
+) -> Option<u64> {
+    let liab_value =
+        (token_value(asset_amount, asset_mint_decimals, asset_price) * (BASIS_POINTS as f64)) /
+        ((BASIS_POINTS + bonus_bps) as f64);
+
+    token_amount(liab_value, liab_mint_decimals, liab_price)
+}
+
+/// Most of `liability_amount` one liquidation may repay
+pub fn close_factor_liab_amount(liability_amount: u64, close_factor_bps: u16) -> u128 {
+    ((liability_amount as u128) * (close_factor_bps as u128)) / (BASIS_POINTS as u128)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skewed_pool_does_not_raise_lp_token_price() {
+        // 1k coin at 10 against 10k pc at 1, then the same `x * y = k` skewed by a swap that
+        // dumps pc into the pool
+        let (coin_price, pc_price) = (10.0, 1.0);
+        let balanced = lp_token_price(
+            token_value(1_000_000_000, 6, coin_price),
+            token_value(10_000_000_000, 6, pc_price),
+            100_000_000_000,
+            9
+        );
+        let skewed = lp_token_price(
+            token_value(500_000_000, 6, coin_price),
+            token_value(20_000_000_000, 6, pc_price),
+            100_000_000_000,
+            9
+        );
+
+        assert_eq!(balanced, 200.0);
+        assert!(skewed <= balanced);
+
+        // Summing the skewed reserve values would price it at 250
+        let skewed_pool_value =
+            token_value(500_000_000, 6, coin_price) + token_value(20_000_000_000, 6, pc_price);
+        assert_eq!(skewed_pool_value / 100.0, 250.0);
+    }
+
+    #[test]
+    fn sqrt_matches_std() {
+        extern crate std;
+
+        for x in [5e-324, 1e-310, 1e-12, 0.25, 2.0, 3.0, 10.0, 1e4, 123_456.789, 2e15, 1e300] {
+            assert_eq!(sqrt(x), std::primitive::f64::sqrt(x), "sqrt({})", x);
+        }
+        assert_eq!(sqrt(0.0), 0.0);
+        assert!(sqrt(-1.0).is_nan());
+    }
+
+    #[test]
+    fn empty_pool_lp_token_price_is_zero() {
+        assert_eq!(lp_token_price(0.0, 0.0, 0, 9), 0.0);
+    }
+
+    #[test]
+    fn shares_round_down_and_check_overflow() {
+        assert_eq!(amount_to_shares(1_000, 3), Some(333));
+        assert_eq!(shares_to_amount(333, 3), Some(999));
+        assert_eq!(amount_to_shares(1_000, 0), None);
+        assert_eq!(shares_to_amount(u64::MAX, 2), None);
+    }
+
+    #[test]
+    fn utilization_of_empty_bank_is_zero() {
+        assert_eq!(utilization_ratio(0, 0), 0.0);
+        assert_eq!(utilization_ratio(1_000, 250), 0.25);
+        assert_eq!(accrual_utilization_ratio(1_000, 250), 0.25);
+    }
+
+    #[test]
+    fn share_value_accrues_simple_interest() {
+        let year = SECONDS_PER_YEAR as u64;
+
+        assert_eq!(accrue_share_value(1_000_000_000, 0.1, year), 1_100_000_000);
+        // f32 only has 24 bits of mantissa
+        assert_eq!(accrue_share_value(1_000_000_000, 0.1, year / 2), 1_049_999_936);
+        assert_eq!(accrue_share_value(1_000_000_000, 0.1, 0), 1_000_000_000);
+        // Truncated back to the initial share value of new banks
+        assert_eq!(accrue_share_value(1, 0.1, year), 1);
+    }
+
+    #[test]
+    fn linear_curve_reaches_plateau_at_optimal_utilization() {
+        assert_eq!(linear_interest_rate(0.0, 0.8, 0.1, 1.0), 0.0);
+        assert_eq!(linear_interest_rate(0.4, 0.8, 0.1, 1.0), 0.05);
+        assert_eq!(linear_interest_rate(0.8, 0.8, 0.1, 1.0), 0.1);
+        assert_eq!(linear_interest_rate(1.0, 0.8, 0.1, 1.0), 1.0);
+        assert_eq!(interest_rates(0.1, 0.5), (0.05, 0.1));
+    }
+
+    #[test]
+    fn multi_kink_curve_runs_through_every_kink() {
+        let kinks = [(0.5, 0.05), (0.8, 0.15)];
+        let rate = |ur| multi_kink_interest_rate(ur, 0.01, kinks, 1.0);
+
+        assert_eq!(rate(0.0), 0.01);
+        assert!((rate(0.25) - 0.03).abs() < 1e-6);
+        assert!((rate(0.5) - 0.05).abs() < 1e-6);
+        assert!((rate(0.65) - 0.1).abs() < 1e-6);
+        assert!((rate(0.8) - 0.15).abs() < 1e-6);
+        assert!((rate(1.0) - 1.0).abs() < 1e-6);
+        assert_eq!(multi_kink_interest_rate(0.5, 0.0, [], 1.0), 0.5);
+    }
+
+    #[test]
+    fn adaptive_curve_pivots_around_rate_at_target() {
+        assert_eq!(adaptive_interest_rate(0.0, 0.8, 0.1), 0.1 / ADAPTIVE_RATE_CURVE_STEEPNESS);
+        assert_eq!(adaptive_interest_rate(0.8, 0.8, 0.1), 0.1);
+        assert_eq!(adaptive_interest_rate(1.0, 0.8, 0.1), 0.1 * ADAPTIVE_RATE_CURVE_STEEPNESS);
+        assert_eq!(adaptive_interest_rate(1.5, 0.8, 0.1), 0.1 * ADAPTIVE_RATE_CURVE_STEEPNESS);
+
+        assert_eq!(utilization_error(0.0, 0.8), -1.0);
+        assert_eq!(utilization_error(0.8, 0.8), 0.0);
+        assert_eq!(utilization_error(1.2, 0.8), 1.0);
+
+        // Proportional step on the error change plus half a year of the integral term
+        let half_year = (SECONDS_PER_YEAR as u64) / 2;
+        assert_eq!(adaptive_rate_at_target(0.1, 0.0, 0.5, 0.05, 5.0, half_year), 1.375);
+        assert_eq!(adaptive_rate_at_target(0.1, 0.5, 0.5, 0.05, 5.0, 0), 0.1);
+    }
+
+    #[test]
+    fn auction_bonus_rises_linearly_to_max() {
+        assert_eq!(auction_liquidation_bonus_bps(1_000, 100, 0), 0);
+        assert_eq!(auction_liquidation_bonus_bps(1_000, 100, 25), 250);
+        assert_eq!(auction_liquidation_bonus_bps(1_000, 100, 100), 1_000);
+        assert_eq!(auction_liquidation_bonus_bps(1_000, 100, 10_000), 1_000);
+    }
+
+    #[test]
+    fn close_factor_liab_amount_does_not_overflow() {
+        assert_eq!(close_factor_liab_amount(1_000, 5_000), 500);
+        assert_eq!(close_factor_liab_amount(999, 5_000), 499);
+        assert_eq!(close_factor_liab_amount(u64::MAX, 10_000), u64::MAX as u128);
+    }
+
+    #[test]
+    fn token_amount_inverts_token_value() {
+        assert_eq!(token_amount(token_value(2_500_000, 6, 0.5), 6, 0.5), Some(2_500_000));
+        assert_eq!(token_amount(300.0, 9, 150.0), Some(2_000_000_000));
+        assert_eq!(token_amount(1.0, 6, 0.0), None);
+        assert_eq!(token_amount(1.0, 6, -1.0), None);
+        assert_eq!(token_amount(f64::MAX, 6, 1.0), None);
+    }
+}
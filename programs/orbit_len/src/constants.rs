@@ -1,3 +1,7 @@
+use anchor_lang::prelude::*;
+
+pub use orbit_len_math::{ ADAPTIVE_RATE_CURVE_STEEPNESS, BASIS_POINTS, SECONDS_PER_YEAR };
+
 pub const LIQUIDITY_VAULT_AUTHORITY_SEED: &str = "liquidity_vault_auth";
 pub const INSURANCE_VAULT_AUTHORITY_SEED: &str = "insurance_vault_auth";
 
@@ -6,8 +10,31 @@ pub const INSURANCE_VAULT_SEED: &str = "insurance_vault";
 
 pub const BANK_SEED: &str = "bank";
 
+pub const CLMM_POSITION_AUTHORITY_SEED: &str = "clmm_position_auth";
+
 pub const ORBITLEN_ACCOUNT_SEED: &str = "orbitlen_account";
 
-pub const SECONDS_PER_YEAR: usize = 31_536_000;
+pub const EMODE_CATEGORY_SEED: &str = "emode_category";
+
+pub const MAX_INTEREST_RATE_KINKS: usize = 4;
+
+/// How far above its maintenance requirement a liquidation may leave the liquidatee, in basis
+/// points of its weighted liabilities
+pub const LIQUIDATION_MAX_HEALTH_BUFFER_BPS: u32 = 500;
+
+/// E-mode category of banks and accounts outside of any category
+pub const EMODE_CATEGORY_NONE: u8 = 0;
+
+/// coin feed, pc feed, amm, amm open orders, amm coin vault, amm pc vault, lp mint
+pub const RAYDIUM_LP_ORACLE_AIS_LEN: usize = 7;
+
+/// token 0 feed, token 1 feed, pool state, token 0 vault, token 1 vault
+pub const RAYDIUM_CPMM_LP_ORACLE_AIS_LEN: usize = 5;
+
+/// Raydium AMM v4 programs the proxies may CPI into, mainnet and devnet
+pub const RAYDIUM_AMM_PROGRAM_IDS: [Pubkey; 2] = [
+    pubkey!("675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8"),
+    pubkey!("HWy1jotHpo6UqeQxx49dpYYdQB8wj9Qk9MdxwjLvDHB8"),
+];
 
-pub const COMMON_TOKEN_DECIMALS: u8 = 6;
+pub const SPL_MEMO_PROGRAM_ID: Pubkey = pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
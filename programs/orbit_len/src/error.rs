@@ -25,5 +25,41 @@ pub enum OrbitlenError {
     #[msg("Math error on interest rate config")]
     InterestRateConfigMathError,
     #[msg("Invalid token program")]
-    UnsupportedTokenProgram
+    UnsupportedTokenProgram,
+    #[msg("Invalid interest rate config")]
+    InvalidInterestRateConfig,
+    #[msg("Mint extension is not supported")]
+    UnsupportedMintExtension,
+    #[msg("Invalid bank config")]
+    InvalidBankConfig,
+    #[msg("Bank does not allow borrowing")]
+    BankNotBorrowable,
+    #[msg("Missing oracle accounts")]
+    MissingOracleAccounts,
+    #[msg("Invalid Raydium pool account")]
+    InvalidRaydiumPoolAccount,
+    #[msg("Invalid or incomplete bank leg accounts")]
+    InvalidBankLegAccounts,
+    #[msg("Slippage tolerance exceeded")]
+    SlippageToleranceExceeded,
+    #[msg("Raydium program is not whitelisted")]
+    InvalidRaydiumProgram,
+    #[msg("Invalid or missing health check accounts")]
+    InvalidHealthAccounts,
+    #[msg("Account health is below the required margin")]
+    InsufficientAccountHealth,
+    #[msg("Isolated bank liability can not be held next to other liabilities")]
+    IsolatedAccountIllegalState,
+    #[msg("Account is healthy and can not be liquidated")]
+    HealthyAccount,
+    #[msg("Liquidation repays more than the close factor allows")]
+    CloseFactorExceeded,
+    #[msg("Liquidation leaves the account healthier than the target")]
+    LiquidationOvershoot,
+    #[msg("Signer is not the program upgrade authority")]
+    Unauthorized,
+    #[msg("Invalid e-mode category config")]
+    InvalidEmodeConfig,
+    #[msg("E-mode category account missing or not the account's category")]
+    InvalidEmodeCategory,
 }
@@ -13,6 +13,25 @@ pub struct LendingAccountLiquidateEvent {
     pub post_balances: LiquidationBalances,
 }
 
+#[event]
+pub struct LendingAccountLiquidateWithTokensEvent {
+    pub liquidator: Pubkey,
+    pub liquidatee_orbitlen_account: Pubkey,
+    pub liquidatee_orbitlen_account_authority: Pubkey,
+    pub asset_bank: Pubkey,
+    pub asset_mint: Pubkey,
+    pub liability_bank: Pubkey,
+    pub liability_mint: Pubkey,
+    /// collateral paid out of the asset bank vault to the liquidator
+    pub asset_amount: u64,
+    /// liability repaid into the liab bank vault on behalf of the liquidatee
+    pub liability_amount: u64,
+    pub liquidatee_asset_pre_balance: f64,
+    pub liquidatee_asset_post_balance: f64,
+    pub liquidatee_liability_pre_balance: f64,
+    pub liquidatee_liability_post_balance: f64,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize)]
 pub struct LiquidationBalances {
     pub liquidatee_asset_balance: f64,
@@ -35,6 +54,34 @@ pub struct LendingPoolBankCreateEvent {
     pub mint: Pubkey,
 }
 
+#[event]
+pub struct LendingPoolConfigureEmodeCategoryEvent {
+    pub signer: Pubkey,
+    pub emode_category: Pubkey,
+    pub category: u8,
+    pub asset_weight_init_bps: u16,
+    pub asset_weight_maint_bps: u16,
+    pub liability_weight_init_bps: u16,
+    pub liability_weight_maint_bps: u16,
+}
+
+#[event]
+pub struct BankInterestAccruedEvent {
+    pub bank: Pubkey,
+    pub mint: Pubkey,
+    pub time_delta: u64,
+    pub total_assets: u64,
+    pub total_liabilities: u64,
+    pub utilization_rate: f64,
+    pub old_asset_share_value: u64,
+    pub old_liability_share_value: u64,
+    pub new_asset_share_value: u64,
+    pub new_liability_share_value: u64,
+    pub lending_apr: f64,
+    pub borrowing_apr: f64,
+    pub fees: u64,
+}
+
 #[event]
 pub struct OrbitlenAccountCreateEvent {
     pub header: AccountEventHeader,
@@ -48,6 +95,19 @@ pub struct LendingAccountDepositEvent {
     pub amount: u64,
 }
 
+#[event]
+pub struct LendingAccountSetEmodeEvent {
+    pub header: AccountEventHeader,
+    pub emode_category: u8,
+}
+
+#[event]
+pub struct LendingAccountLiquidationAuctionEvent {
+    pub header: AccountEventHeader,
+    /// 0 when the account is healthy and its auction was reset
+    pub liquidation_auction_start_slot: u64,
+}
+
 #[event]
 pub struct LendingAccountBorrowEvent {
     pub header: AccountEventHeader,
@@ -75,4 +135,49 @@ pub struct RaydiumDepositEvent {
     pub coin_mint: Pubkey,
     pub coin_amount: u64,
     pub pc_amount: u64,
+    pub lp_amount: u64,
+}
+
+#[event]
+pub struct RaydiumWithdrawEvent {
+    pub header: RaydiumEventHeader,
+    pub coin_mint: Pubkey,
+    pub lp_amount: u64,
+    pub coin_amount: u64,
+    pub pc_amount: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub enum RaydiumSwapDirection {
+    CoinToPc,
+    PcToCoin,
+}
+
+#[event]
+pub struct RaydiumSwapEvent {
+    pub header: RaydiumEventHeader,
+    pub coin_mint: Pubkey,
+    pub direction: RaydiumSwapDirection,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// pc per coin in whole tokens, the native amounts scaled by the pool's coin and pc
+    /// decimals
+    pub executed_price: f64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize)]
+pub struct RaydiumCpmmEventHeader {
+    pub pool_state: Pubkey,
+    pub signer: Pubkey,
+    pub orbitlen_account: Pubkey,
+    pub orbitlen_account_authority: Pubkey,
+}
+
+#[event]
+pub struct RaydiumCpmmDepositEvent {
+    pub header: RaydiumCpmmEventHeader,
+    pub coin_mint: Pubkey,
+    pub token_0_amount: u64,
+    pub token_1_amount: u64,
+    pub lp_amount: u64,
 }
@@ -0,0 +1,29 @@
+use crate::{ error::OrbitlenError, state::* };
+use anchor_lang::prelude::*;
+use solana_program::{ clock::Clock, sysvar::Sysvar };
+
+/// Permissionless crank bringing a bank's share values up to date without touching any
+/// lending account. Always emits a `BankInterestAccruedEvent`, with a zero `time_delta` and
+/// the current state when the bank was already accrued this second.
+pub fn lending_pool_accrue_bank_interest_process(
+    ctx: Context<LendingPoolAccrueBankInterest>
+) -> Result<()> {
+    let clock = Clock::get().map_err(|_| OrbitlenError::GetClockFailed)?;
+    let bank_pk = ctx.accounts.bank.key();
+
+    let mut bank = ctx.accounts.bank.load_mut()?;
+
+    let event = match bank.apply_interest_accrual(clock.unix_timestamp, &bank_pk)? {
+        Some(event) => event,
+        None => bank.get_current_interest_event(&bank_pk)?,
+    };
+    emit!(event);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct LendingPoolAccrueBankInterest<'info> {
+    #[account(mut)]
+    pub bank: AccountLoader<'info, Bank>,
+}
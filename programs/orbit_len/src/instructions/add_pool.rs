@@ -1,12 +1,12 @@
-use crate::{ constants::*, events::*, state::* };
-use anchor_lang::prelude::*;
+use crate::{ constants::*, error::OrbitlenError, events::*, state::*, utils };
+use anchor_lang::{ prelude::*, solana_program::bpf_loader_upgradeable };
 use anchor_spl::token_interface::*;
 
 pub fn lending_pool_add_bank_process(
     ctx: Context<LendingPoolAddBank>,
     bank_config: BankConfig
 ) -> Result<()> {
-    msg!("Adding bank to lending pool");
+    debug!("Adding bank to lending pool");
     let LendingPoolAddBank {
         bank_mint,
         liquidity_vault,
@@ -15,7 +15,10 @@ pub fn lending_pool_add_bank_process(
     } = ctx.accounts;
 
     let mut bank = bank_loader.load_init()?;
-    msg!("bank_config: {:?}", bank_config);
+    debug!("bank_config: {:?}", bank_config);
+
+    bank_config.validate()?;
+    utils::validate_bank_mint_extensions(&bank_mint.to_account_info())?;
 
     let liquidity_vault_bump = ctx.bumps.liquidity_vault;
     let liquidity_vault_authority_bump = ctx.bumps.liquidity_vault_authority;
@@ -43,6 +46,14 @@ pub fn lending_pool_add_bank_process(
 pub struct LendingPoolAddBank<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
+    /// Only the upgrade authority of the program can list banks
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ OrbitlenError::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
     pub bank_mint: Box<InterfaceAccount<'info, Mint>>,
     #[account(
         init,
@@ -70,7 +81,7 @@ pub struct LendingPoolAddBank<'info> {
 }
 
 pub fn initial_vault_process(_ctx: Context<InitialVault>, _bank: Pubkey) -> Result<()> {
-    msg!("Initial Vault");
+    debug!("Initial Vault");
 
     Ok(())
 }
@@ -80,6 +91,14 @@ pub fn initial_vault_process(_ctx: Context<InitialVault>, _bank: Pubkey) -> Resu
 pub struct InitialVault<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
+    /// Only the upgrade authority of the program can list banks
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ OrbitlenError::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
     pub bank_mint: Box<InterfaceAccount<'info, Mint>>,
     /// CHECK: ⋐ ͡⋄ ω ͡⋄ ⋑
     #[account(seeds = [LIQUIDITY_VAULT_AUTHORITY_SEED.as_bytes(), bank.key().as_ref()], bump)]
@@ -96,3 +115,60 @@ pub struct InitialVault<'info> {
     pub token_program: Interface<'info, TokenInterface>,
     pub system_program: Program<'info, System>,
 }
+
+/// Creates or updates the weights of an e-mode category. Banks are listed in the category
+/// through their config, accounts opted into it are valued with the new weights right away.
+pub fn lending_pool_configure_emode_category_process(
+    ctx: Context<LendingPoolConfigureEmodeCategory>,
+    category: u8,
+    emode_config: EmodeConfig
+) -> Result<()> {
+    let LendingPoolConfigureEmodeCategory { admin, emode_category: emode_category_loader, .. } =
+        ctx.accounts;
+
+    require_neq!(category, EMODE_CATEGORY_NONE, OrbitlenError::InvalidEmodeCategory);
+    emode_config.validate()?;
+
+    // `init_if_needed` leaves the discriminator of a new account unset
+    let mut emode_category = emode_category_loader
+        .load_init()
+        .or_else(|_| emode_category_loader.load_mut())?;
+    emode_category.category = category;
+    emode_category.config = emode_config;
+
+    emit!(LendingPoolConfigureEmodeCategoryEvent {
+        signer: admin.key(),
+        emode_category: emode_category_loader.key(),
+        category,
+        asset_weight_init_bps: emode_config.asset_weight_init_bps,
+        asset_weight_maint_bps: emode_config.asset_weight_maint_bps,
+        liability_weight_init_bps: emode_config.liability_weight_init_bps,
+        liability_weight_maint_bps: emode_config.liability_weight_maint_bps,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+#[instruction(category: u8)]
+pub struct LendingPoolConfigureEmodeCategory<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    /// Only the upgrade authority of the program can set risk weights
+    #[account(
+        seeds = [crate::ID.as_ref()],
+        bump,
+        seeds::program = bpf_loader_upgradeable::ID,
+        constraint = program_data.upgrade_authority_address == Some(admin.key()) @ OrbitlenError::Unauthorized,
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(
+        init_if_needed,
+        seeds = [EMODE_CATEGORY_SEED.as_bytes(), &[category]],
+        bump,
+        space = 8 + EmodeCategory::INIT_SPACE,
+        payer = admin
+    )]
+    pub emode_category: AccountLoader<'info, EmodeCategory>,
+    pub system_program: Program<'info, System>,
+}
@@ -3,6 +3,16 @@ use anchor_lang::prelude::*;
 use anchor_spl::token_interface::*;
 use solana_program::{ clock::Clock, sysvar::Sysvar };
 
+/// Withdraws `amount` from the account's deposit in the bank and borrows whatever it doesn't
+/// cover. The account has to stay above its initial margin afterwards.
+///
+/// Expected remaining account schema, see `RiskEngine::calc_account_health` for the health
+/// accounts, which include the bank
+/// [
+///    bank_mint_ai,
+///    ...health_ais,
+///    ...transfer_hook_extra_ais,
+///  ]
 pub fn lending_account_borrow_process<'info>(
     mut ctx: Context<'_, '_, 'info, 'info, LendingAccountBorrow<'info>>,
     amount: u64
@@ -26,11 +36,20 @@ pub fn lending_account_borrow_process<'info>(
 
     let mut orbitlen_account = orbitlen_account_loader.load_mut()?;
 
-    bank_loader.load_mut()?.accrue_interest(clock.unix_timestamp)?;
+    let (health_ais, transfer_hook_ais) = RiskEngine::split_health_ais(
+        &orbitlen_account,
+        &[bank_loader.key()],
+        ctx.remaining_accounts
+    )?;
+
+    bank_loader.load_mut()?.accrue_interest(clock.unix_timestamp, &bank_loader.key())?;
 
     {
         let mut bank = bank_loader.load_mut()?;
 
+        // Collateral-only banks (e.g. escrowed LP) can not be withdrawn from either
+        require!(bank.config.is_borrowable(), OrbitlenError::BankNotBorrowable);
+
         let liquidity_vault_authority_bump = bank.liquidity_vault_authority_bump;
 
         let mut bank_account = BankAccountWrapper::find_or_create(
@@ -56,7 +75,7 @@ pub fn lending_account_borrow_process<'info>(
             &maybe_bank_mint,
             token_program.to_account_info(),
             signer_seeds,
-            ctx.remaining_accounts
+            transfer_hook_ais
         )?;
 
         emit!(LendingAccountBorrowEvent {
@@ -71,6 +90,9 @@ pub fn lending_account_borrow_process<'info>(
         });
     }
 
+    RiskEngine::check_account_health(&orbitlen_account, health_ais, RiskRequirementType::Initial)?;
+    orbitlen_account.reset_liquidation_auction();
+
     Ok(())
 }
 
@@ -4,6 +4,18 @@ use anchor_spl::token_interface::TokenInterface;
 use solana_program::clock::Clock;
 use solana_program::sysvar::Sysvar;
 
+/// The deposited amount is credited net of any Token-2022 transfer fee. Deposits also repay
+/// liabilities, so while the account's liquidation auction is running they take its health
+/// accounts and reset the auction once the account is back above its maintenance
+/// requirement. A deposit leaving it liquidatable leaves the auction running.
+///
+/// Expected remaining account schema, see `RiskEngine::calc_account_health` for the health
+/// accounts, which include the bank
+/// [
+///    bank_mint_ai,
+///    ...health_ais, only while the liquidation auction is running
+///    ...transfer_hook_extra_ais,
+///  ]
 pub fn lending_account_deposit_process<'info>(
     mut ctx: Context<'_, '_, 'info, 'info, LendingAccountDeposit<'info>>,
     amount: u64
@@ -26,11 +38,22 @@ pub fn lending_account_deposit_process<'info>(
     )?;
 
     let mut bank = bank_loader.load_mut()?;
-    msg!("bank: {:?}", bank);
+    debug!("bank: {:?}", bank);
     let mut orbitlen_account = orbitlen_account_loader.load_mut()?;
-    msg!("orbitlen_account: {:?}", orbitlen_account);
+    debug!("orbitlen_account: {:?}", orbitlen_account);
 
-    bank.accrue_interest(clock.unix_timestamp)?;
+    let liquidation_auction_running = orbitlen_account.liquidation_auction_start_slot != 0;
+    let (health_ais, transfer_hook_ais) = if liquidation_auction_running {
+        RiskEngine::split_health_ais(
+            &orbitlen_account,
+            &[bank_loader.key()],
+            ctx.remaining_accounts
+        )?
+    } else {
+        (&[][..], ctx.remaining_accounts)
+    };
+
+    bank.accrue_interest(clock.unix_timestamp, &bank_loader.key())?;
 
     let mut bank_account = BankAccountWrapper::find_or_create(
         &bank_loader.key(),
@@ -38,7 +61,13 @@ pub fn lending_account_deposit_process<'info>(
         &mut orbitlen_account.lending_account
     )?;
 
-    bank_account.deposit(amount.into())?;
+    let amount_post_fee = utils::calculate_post_fee_spl_deposit_amount(
+        maybe_bank_mint.to_account_info(),
+        amount,
+        clock.epoch
+    )?;
+
+    bank_account.deposit(amount_post_fee)?;
 
     bank_account.deposit_spl_transfer(
         amount,
@@ -47,9 +76,21 @@ pub fn lending_account_deposit_process<'info>(
         signer.to_account_info(),
         &maybe_bank_mint,
         token_program.to_account_info(),
-        ctx.remaining_accounts
+        transfer_hook_ais
     )?;
 
+    if liquidation_auction_running {
+        let health = RiskEngine::calc_account_health(
+            &orbitlen_account,
+            health_ais,
+            RiskRequirementType::Maintenance
+        )?;
+
+        if health.is_healthy() {
+            orbitlen_account.reset_liquidation_auction();
+        }
+    }
+
     emit!(LendingAccountDepositEvent {
         header: AccountEventHeader {
             signer: signer.key(),
@@ -58,7 +99,7 @@ pub fn lending_account_deposit_process<'info>(
         },
         bank: bank_loader.key(),
         mint: bank.mint,
-        amount,
+        amount: amount_post_fee,
     });
 
     Ok(())
@@ -0,0 +1,490 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenInterface };
+use crate::{ bank::*, account::*, error::*, events::*, risk_engine::*, utils };
+
+/// One side of a pool as seen by the proxies: the user token account the venue moves tokens
+/// through and the bank those tokens are sourced from and settled back into.
+pub struct BankLeg<'a, 'info> {
+    pub bank: &'a AccountLoader<'info, Bank>,
+    pub bank_liquidity_vault: AccountInfo<'info>,
+    pub bank_liquidity_vault_authority: AccountInfo<'info>,
+    pub mint: &'a InterfaceAccount<'info, Mint>,
+    pub token_program: AccountInfo<'info>,
+    pub user_token_account: AccountInfo<'info>,
+}
+
+impl<'a, 'info> BankLeg<'a, 'info> {
+    /// Builds the leg from optional accounts, all of them must be either present or absent.
+    /// Bank, vault and vault authority are checked against each other here since optional
+    /// accounts can't carry seed constraints on other optional accounts.
+    pub fn try_from_optional(
+        bank: Option<&'a AccountLoader<'info, Bank>>,
+        bank_liquidity_vault: Option<&AccountInfo<'info>>,
+        bank_liquidity_vault_authority: Option<&AccountInfo<'info>>,
+        mint: Option<&'a InterfaceAccount<'info, Mint>>,
+        token_program: Option<&Interface<'info, TokenInterface>>,
+        user_token_account: Option<AccountInfo<'info>>
+    ) -> Result<Option<Self>> {
+        match
+            (
+                bank,
+                bank_liquidity_vault,
+                bank_liquidity_vault_authority,
+                mint,
+                token_program,
+                user_token_account,
+            )
+        {
+            (None, None, None, None, None, _) => Ok(None),
+            (
+                Some(bank),
+                Some(bank_liquidity_vault),
+                Some(bank_liquidity_vault_authority),
+                Some(mint),
+                Some(token_program),
+                Some(user_token_account),
+            ) => {
+                {
+                    let bank_data = bank.load()?;
+
+                    require_keys_eq!(bank_data.mint, mint.key(), OrbitlenError::InvalidBankLegAccounts);
+                    require_keys_eq!(
+                        bank_data.liquidity_vault,
+                        bank_liquidity_vault.key(),
+                        OrbitlenError::InvalidBankLegAccounts
+                    );
+
+                    let liquidity_vault_authority = Pubkey::create_program_address(
+                        &[
+                            BankVaultType::Liquidity.get_authority_seed(),
+                            bank.key().as_ref(),
+                            &[bank_data.liquidity_vault_authority_bump],
+                        ],
+                        &crate::ID
+                    ).map_err(|_| OrbitlenError::InvalidBankLegAccounts)?;
+                    require_keys_eq!(
+                        liquidity_vault_authority,
+                        bank_liquidity_vault_authority.key(),
+                        OrbitlenError::InvalidBankLegAccounts
+                    );
+                    require_keys_eq!(
+                        *mint.to_account_info().owner,
+                        token_program.key(),
+                        OrbitlenError::InvalidBankLegAccounts
+                    );
+                }
+
+                Ok(
+                    Some(BankLeg {
+                        bank,
+                        bank_liquidity_vault: bank_liquidity_vault.clone(),
+                        bank_liquidity_vault_authority: bank_liquidity_vault_authority.clone(),
+                        mint,
+                        token_program: token_program.to_account_info(),
+                        user_token_account,
+                    })
+                )
+            }
+            _ => err!(OrbitlenError::InvalidBankLegAccounts),
+        }
+    }
+
+    pub fn get_user_token_amount(&self) -> Result<u64> {
+        utils::get_token_account_amount(&self.user_token_account)
+    }
+
+    /// user token account --> bank vault, returns the amount credited net of transfer fees
+    pub fn deposit(
+        &self,
+        orbitlen_account_loader: &AccountLoader<'info, OrbitlenAccount>,
+        user_owner: &AccountInfo<'info>,
+        amount: u64,
+        remaining_accounts: &[AccountInfo<'info>]
+    ) -> Result<u64> {
+        let clock = Clock::get().map_err(|_| OrbitlenError::GetClockFailed)?;
+
+        let mut bank = self.bank.load_mut()?;
+        debug!("bank: {:?}", bank);
+        let mut orbitlen_account = orbitlen_account_loader.load_mut()?;
+        debug!("orbitlen_account: {:?}", orbitlen_account);
+
+        bank.accrue_interest(clock.unix_timestamp, &self.bank.key())?;
+
+        let amount_post_fee = utils::calculate_post_fee_spl_deposit_amount(
+            self.mint.to_account_info(),
+            amount,
+            clock.epoch
+        )?;
+
+        let mut bank_account = BankAccountWrapper::find_or_create(
+            &self.bank.key(),
+            &mut bank,
+            &mut orbitlen_account.lending_account
+        )?;
+
+        bank_account.deposit(amount_post_fee)?;
+
+        bank_account.deposit_spl_transfer(
+            amount,
+            self.user_token_account.clone(),
+            self.bank_liquidity_vault.clone(),
+            user_owner.clone(),
+            self.mint,
+            self.token_program.clone(),
+            remaining_accounts
+        )?;
+
+        emit!(LendingAccountDepositEvent {
+            header: AccountEventHeader {
+                signer: user_owner.key(),
+                orbitlen_account: orbitlen_account_loader.key(),
+                orbitlen_account_authority: orbitlen_account.authority,
+            },
+            bank: self.bank.key(),
+            mint: bank.mint,
+            amount: amount_post_fee,
+        });
+
+        Ok(amount_post_fee)
+    }
+
+    /// bank vault --> user token account, withdrawing the user's deposit first and borrowing
+    /// the rest
+    pub fn borrow(
+        &self,
+        orbitlen_account_loader: &AccountLoader<'info, OrbitlenAccount>,
+        user_owner: &AccountInfo<'info>,
+        amount: u64,
+        remaining_accounts: &[AccountInfo<'info>]
+    ) -> Result<()> {
+        let clock = Clock::get().map_err(|_| OrbitlenError::GetClockFailed)?;
+
+        let mut bank = self.bank.load_mut()?;
+        debug!("bank: {:?}", bank);
+        let mut orbitlen_account = orbitlen_account_loader.load_mut()?;
+        debug!("orbitlen_account: {:?}", orbitlen_account);
+
+        bank.accrue_interest(clock.unix_timestamp, &self.bank.key())?;
+        let liquidity_vault_authority_bump = bank.liquidity_vault_authority_bump;
+
+        let mut bank_account = BankAccountWrapper::find_or_create(
+            &self.bank.key(),
+            &mut bank,
+            &mut orbitlen_account.lending_account
+        )?;
+
+        bank_account.borrow(amount)?;
+
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[
+                BankVaultType::Liquidity.get_authority_seed(),
+                &self.bank.key().to_bytes(),
+                &[liquidity_vault_authority_bump],
+            ],
+        ];
+
+        bank_account.withdraw_spl_transfer(
+            amount,
+            self.bank_liquidity_vault.clone(),
+            self.user_token_account.clone(),
+            self.bank_liquidity_vault_authority.clone(),
+            self.mint,
+            self.token_program.clone(),
+            signer_seeds,
+            remaining_accounts
+        )?;
+
+        emit!(LendingAccountBorrowEvent {
+            header: AccountEventHeader {
+                signer: user_owner.key(),
+                orbitlen_account: orbitlen_account_loader.key(),
+                orbitlen_account_authority: orbitlen_account.authority,
+            },
+            bank: self.bank.key(),
+            mint: bank.mint,
+            amount,
+        });
+
+        Ok(())
+    }
+
+    /// Books the change of the user token account over a venue CPI: an increase is
+    /// deposited into the bank, a decrease is sourced from it.
+    pub fn settle(
+        &self,
+        orbitlen_account_loader: &AccountLoader<'info, OrbitlenAccount>,
+        user_owner: &AccountInfo<'info>,
+        amount_before: u64,
+        amount_after: u64,
+        remaining_accounts: &[AccountInfo<'info>]
+    ) -> Result<()> {
+        debug!("settle {}: amount_before: {}, amount_after: {}", self.mint.key(), amount_before, amount_after);
+
+        if amount_after > amount_before {
+            self.deposit(
+                orbitlen_account_loader,
+                user_owner,
+                amount_after - amount_before,
+                remaining_accounts
+            )?;
+        } else if amount_before > amount_after {
+            self.borrow(
+                orbitlen_account_loader,
+                user_owner,
+                amount_before - amount_after,
+                remaining_accounts
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A venue the proxy instructions route through, implemented on their accounts struct.
+///
+/// Implementors map their accounts to a required coin leg and an optional pc leg and build
+/// their CPIs, sourcing borrowed funds and settling the resulting balance changes with the
+/// banks is shared by every venue. Every proxy that may borrow ends with `check_health` once
+/// all of its balances are booked.
+pub trait DexAdapter<'info> {
+    fn get_orbitlen_account(&self) -> &AccountLoader<'info, OrbitlenAccount>;
+
+    /// Owner of the user token accounts of both legs
+    fn get_user_owner(&self) -> AccountInfo<'info>;
+
+    fn get_coin_leg(&self) -> BankLeg<'_, 'info>;
+
+    fn get_pc_leg(&self) -> Result<Option<BankLeg<'_, 'info>>>;
+
+    /// User token account of the pc side, for venues providing liquidity. Without a pc leg
+    /// `borrow_for_cpi` measures the pc amount the venue took on it.
+    fn get_user_pc_token_account(&self) -> Option<AccountInfo<'info>> {
+        None
+    }
+
+    /// Bank the venue's LP tokens are escrowed in, for venues minting or burning them
+    fn get_lp_bank(&self) -> Option<Pubkey> {
+        None
+    }
+
+    /// Venue specific checks of the passed accounts, run before any funds move
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Fails if the Orbitlen account is under its initial margin, the health accounts of
+    /// every active balance lead `remaining_accounts`, see `RiskEngine::calc_account_health`.
+    /// A passing account is healthy, so any liquidation auction on it is reset.
+    fn check_health(&self, remaining_accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let mut orbitlen_account = self.get_orbitlen_account().load_mut()?;
+
+        RiskEngine::check_account_health(
+            &orbitlen_account,
+            remaining_accounts,
+            RiskRequirementType::Initial
+        )?;
+        orbitlen_account.reset_liquidation_auction();
+
+        Ok(())
+    }
+
+    /// Splits `remaining_accounts` into the health accounts and whatever follows them for the
+    /// venue, e.g. transfer hook extra accounts. The health accounts are counted for the
+    /// balances the account holds once both legs and any LP tokens are settled.
+    fn split_health_accounts(
+        &self,
+        remaining_accounts: &'info [AccountInfo<'info>]
+    ) -> Result<(&'info [AccountInfo<'info>], &'info [AccountInfo<'info>])> {
+        let extra_banks: Vec<Pubkey> = [
+            Some(self.get_coin_leg().bank.key()),
+            self.get_pc_leg()?.map(|pc_leg| pc_leg.bank.key()),
+            self.get_lp_bank(),
+        ]
+            .into_iter()
+            .flatten()
+            .collect();
+
+        RiskEngine::split_health_ais(
+            &*self.get_orbitlen_account().load()?,
+            &extra_banks,
+            remaining_accounts
+        )
+    }
+
+    /// Runs `cpi` and settles the change of both legs' user token accounts with their banks,
+    /// increases are deposited and decreases borrowed. Used for swaps and withdrawals. Only the
+    /// accounts after the health accounts are passed on as transfer hook extra accounts.
+    ///
+    /// Returns whatever `cpi` returns, e.g. amounts it measured before settlement.
+    fn settle_cpi<T>(
+        &self,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        cpi: impl FnOnce() -> Result<T>
+    ) -> Result<T> {
+        self.validate()?;
+
+        let (_, transfer_hook_ais) = self.split_health_accounts(remaining_accounts)?;
+
+        let coin_leg = self.get_coin_leg();
+        let pc_leg = self.get_pc_leg()?;
+        let user_owner = self.get_user_owner();
+
+        let coin_amount_before = coin_leg.get_user_token_amount()?;
+        let pc_amount_before = match &pc_leg {
+            Some(pc_leg) => pc_leg.get_user_token_amount()?,
+            None => 0,
+        };
+        debug!("coin_amount_before: {}, pc_amount_before: {}", coin_amount_before, pc_amount_before);
+
+        let cpi_result = cpi()?;
+
+        coin_leg.settle(
+            self.get_orbitlen_account(),
+            &user_owner,
+            coin_amount_before,
+            coin_leg.get_user_token_amount()?,
+            transfer_hook_ais
+        )?;
+
+        if let Some(pc_leg) = &pc_leg {
+            pc_leg.settle(
+                self.get_orbitlen_account(),
+                &user_owner,
+                pc_amount_before,
+                pc_leg.get_user_token_amount()?,
+                transfer_hook_ais
+            )?;
+        }
+
+        Ok(cpi_result)
+    }
+
+    /// Borrows `coin_amount`, and `pc_amount` when there is a pc leg, into the user token
+    /// accounts, runs `cpi` and returns whatever it didn't take to the banks. Used for
+    /// providing liquidity. Only the accounts after the health accounts are passed on as
+    /// transfer hook extra accounts.
+    ///
+    /// Returns the coin and pc amounts taken by the venue. Without a pc leg the user provides
+    /// the pc side, and the pc amount is measured on `get_user_pc_token_account`, 0 for
+    /// venues without one.
+    fn borrow_for_cpi(
+        &self,
+        coin_amount: u64,
+        pc_amount: u64,
+        remaining_accounts: &'info [AccountInfo<'info>],
+        cpi: impl FnOnce() -> Result<()>
+    ) -> Result<(u64, u64)> {
+        self.validate()?;
+
+        let (_, transfer_hook_ais) = self.split_health_accounts(remaining_accounts)?;
+
+        let coin_leg = self.get_coin_leg();
+        let pc_leg = self.get_pc_leg()?;
+        let user_owner = self.get_user_owner();
+        let orbitlen_account = self.get_orbitlen_account();
+
+        // coin vault --> user coin token account
+        let coin_amount_initial = coin_leg.get_user_token_amount()?;
+        coin_leg.borrow(orbitlen_account, &user_owner, coin_amount, transfer_hook_ais)?;
+        let coin_amount_before = coin_leg.get_user_token_amount()?;
+
+        // pc vault --> user pc token account
+        let (pc_amount_initial, pc_amount_before) = match &pc_leg {
+            Some(pc_leg) => {
+                let pc_amount_initial = pc_leg.get_user_token_amount()?;
+                pc_leg.borrow(orbitlen_account, &user_owner, pc_amount, transfer_hook_ais)?;
+                (pc_amount_initial, pc_leg.get_user_token_amount()?)
+            }
+            None => (0, 0),
+        };
+
+        // without a pc leg the user provides the pc side from their own token account
+        let user_pc_before = match (&pc_leg, self.get_user_pc_token_account()) {
+            (None, Some(user_pc_token_account)) => {
+                let amount = utils::get_token_account_amount(&user_pc_token_account)?;
+                Some((user_pc_token_account, amount))
+            }
+            _ => None,
+        };
+
+        cpi()?;
+
+        // venues only take amounts matching the pool ratio, whatever was borrowed but not
+        // used goes straight back to the bank
+        let coin_amount_used = repay_unused(
+            &coin_leg,
+            orbitlen_account,
+            &user_owner,
+            coin_amount_initial,
+            coin_amount_before,
+            transfer_hook_ais
+        )?;
+
+        let pc_amount_used = match &pc_leg {
+            Some(pc_leg) =>
+                repay_unused(
+                    pc_leg,
+                    orbitlen_account,
+                    &user_owner,
+                    pc_amount_initial,
+                    pc_amount_before,
+                    transfer_hook_ais
+                )?,
+            None =>
+                match &user_pc_before {
+                    Some((user_pc_token_account, amount_before)) =>
+                        amount_before.saturating_sub(
+                            utils::get_token_account_amount(user_pc_token_account)?
+                        ),
+                    None => 0,
+                }
+        };
+
+        Ok((coin_amount_used, pc_amount_used))
+    }
+}
+
+/// Runs a swap `cpi` and returns the amounts that left `source` and reached `destination`
+pub fn measure_swap(
+    source: &AccountInfo<'_>,
+    destination: &AccountInfo<'_>,
+    cpi: impl FnOnce() -> Result<()>
+) -> Result<(u64, u64)> {
+    let source_amount_before = utils::get_token_account_amount(source)?;
+    let destination_amount_before = utils::get_token_account_amount(destination)?;
+
+    cpi()?;
+
+    let amount_in = source_amount_before
+        .checked_sub(utils::get_token_account_amount(source)?)
+        .ok_or(OrbitlenError::MathError)?;
+    let amount_out = utils::get_token_account_amount(destination)?
+        .checked_sub(destination_amount_before)
+        .ok_or(OrbitlenError::MathError)?;
+    debug!("amount_in: {}, amount_out: {}", amount_in, amount_out);
+
+    Ok((amount_in, amount_out))
+}
+
+/// Returns the amount the venue took out of the leg's user token account, and deposits what
+/// is left of the borrowed amount back into the bank.
+fn repay_unused<'info>(
+    leg: &BankLeg<'_, 'info>,
+    orbitlen_account: &AccountLoader<'info, OrbitlenAccount>,
+    user_owner: &AccountInfo<'info>,
+    amount_initial: u64,
+    amount_before: u64,
+    remaining_accounts: &[AccountInfo<'info>]
+) -> Result<u64> {
+    let amount_used = amount_before
+        .checked_sub(leg.get_user_token_amount()?)
+        .ok_or(OrbitlenError::MathError)?;
+    let amount_unused = (amount_before - amount_initial).saturating_sub(amount_used);
+    debug!("{}: amount_used: {}, amount_unused: {}", leg.mint.key(), amount_used, amount_unused);
+
+    if amount_unused > 0 {
+        leg.deposit(orbitlen_account, user_owner, amount_unused, remaining_accounts)?;
+    }
+
+    Ok(amount_used)
+}
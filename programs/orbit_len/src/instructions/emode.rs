@@ -0,0 +1,50 @@
+use crate::{ constants::*, events::*, state::* };
+use anchor_lang::prelude::*;
+
+/// Opts the account into the e-mode `emode_category`, `EMODE_CATEGORY_NONE` opts out.
+/// Switching changes the weights the account is valued with, so it has to stay above its
+/// initial margin afterwards.
+///
+/// Expected remaining account schema, see `RiskEngine::calc_account_health`
+/// [
+///    ...health_ais,
+///  ]
+pub fn lending_account_set_emode_process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, LendingAccountSetEmode<'info>>,
+    emode_category: u8
+) -> Result<()> {
+    let LendingAccountSetEmode { orbitlen_account: orbitlen_account_loader, signer } = ctx.accounts;
+
+    let mut orbitlen_account = orbitlen_account_loader.load_mut()?;
+
+    orbitlen_account.emode_category = emode_category;
+
+    RiskEngine::check_account_health(
+        &orbitlen_account,
+        ctx.remaining_accounts,
+        RiskRequirementType::Initial
+    )?;
+    orbitlen_account.reset_liquidation_auction();
+
+    emit!(LendingAccountSetEmodeEvent {
+        header: AccountEventHeader {
+            signer: signer.key(),
+            orbitlen_account: orbitlen_account_loader.key(),
+            orbitlen_account_authority: orbitlen_account.authority,
+        },
+        emode_category,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct LendingAccountSetEmode<'info> {
+    #[account(
+        mut,
+        seeds = [ORBITLEN_ACCOUNT_SEED.as_bytes(), signer.key().as_ref()],
+        bump,
+    )]
+    pub orbitlen_account: AccountLoader<'info, OrbitlenAccount>,
+    pub signer: Signer<'info>,
+}
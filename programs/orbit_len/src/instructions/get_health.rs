@@ -0,0 +1,130 @@
+use crate::state::*;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct BalanceHealthReport {
+    pub bank: Pubkey,
+    pub mint: Pubkey,
+    pub asset_amount: u64,
+    pub liability_amount: u64,
+    /// Oracle price the balance was valued at
+    pub price: f64,
+    /// Unweighted values in feed currency
+    pub asset_value: f64,
+    pub liability_value: f64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct RequirementHealthReport {
+    /// Weighted values in feed currency
+    pub assets: f64,
+    pub liabilities: f64,
+    /// `assets - liabilities`, negative when the requirement is not met
+    pub health: f64,
+    pub emode: bool,
+}
+
+impl From<AccountHealth> for RequirementHealthReport {
+    fn from(health: AccountHealth) -> Self {
+        Self {
+            assets: health.assets,
+            liabilities: health.liabilities,
+            health: health.assets - health.liabilities,
+            emode: health.emode,
+        }
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq)]
+pub struct LendingAccountHealthReport {
+    pub balances: Vec<BalanceHealthReport>,
+    pub initial: RequirementHealthReport,
+    pub maintenance: RequirementHealthReport,
+}
+
+/// Read-only health query meant for simulation, the Borsh encoded report is set as the
+/// instruction's return data. Balances are valued at the banks' last accrued share values.
+///
+/// Expected remaining account schema, see `RiskEngine::calc_account_health`
+/// [
+///    ...health_ais,
+///  ]
+pub fn lending_account_get_health_process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, LendingAccountGetHealth<'info>>
+) -> Result<LendingAccountHealthReport> {
+    let orbitlen_account = ctx.accounts.orbitlen_account.load()?;
+
+    let (emode_config, bank_ais) = RiskEngine::load_emode_config(
+        &orbitlen_account,
+        ctx.remaining_accounts
+    )?;
+    let balance_values = RiskEngine::calc_balance_values(&orbitlen_account, bank_ais)?;
+
+    Ok(LendingAccountHealthReport {
+        balances: balance_values
+            .iter()
+            .map(|value| BalanceHealthReport {
+                bank: value.bank_pk,
+                mint: value.mint,
+                asset_amount: value.asset_amount,
+                liability_amount: value.liability_amount,
+                price: value.price,
+                asset_value: value.asset_value,
+                liability_value: value.liability_value,
+            })
+            .collect(),
+        initial: RiskEngine::weigh_balance_values(
+            &orbitlen_account,
+            emode_config.as_ref(),
+            &balance_values,
+            RiskRequirementType::Initial
+        ).into(),
+        maintenance: RiskEngine::weigh_balance_values(
+            &orbitlen_account,
+            emode_config.as_ref(),
+            &balance_values,
+            RiskRequirementType::Maintenance
+        ).into(),
+    })
+}
+
+#[derive(Accounts)]
+pub struct LendingAccountGetHealth<'info> {
+    pub orbitlen_account: AccountLoader<'info, OrbitlenAccount>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program::MAX_RETURN_DATA;
+    use bytemuck::Zeroable;
+
+    #[test]
+    fn health_report_fits_in_return_data() {
+        let max_balances = OrbitlenAccount::zeroed().lending_account.balances.len();
+        let requirement = RequirementHealthReport {
+            assets: 0.0,
+            liabilities: 0.0,
+            health: 0.0,
+            emode: false,
+        };
+        let report = LendingAccountHealthReport {
+            balances: vec![
+                BalanceHealthReport {
+                    bank: Pubkey::default(),
+                    mint: Pubkey::default(),
+                    asset_amount: 0,
+                    liability_amount: 0,
+                    price: 0.0,
+                    asset_value: 0.0,
+                    liability_value: 0.0,
+                };
+                max_balances
+            ],
+            initial: requirement.clone(),
+            maintenance: requirement,
+        };
+
+        assert!(report.try_to_vec().unwrap().len() <= MAX_RETURN_DATA);
+    }
+}
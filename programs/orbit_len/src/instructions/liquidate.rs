@@ -1,18 +1,33 @@
+use crate::constants::*;
 use crate::error::OrbitlenError;
 use crate::events::*;
+use crate::math;
 use crate::state::*;
 use anchor_lang::prelude::*;
 use solana_program::clock::Clock;
 use solana_program::sysvar::Sysvar;
 
 /// Calculations:
-/// `q_lf = q_ll = q_a * p_a * 1 / p_l`
+/// `q_lf = q_ll = q_a * p_a * 1 / (p_l * (1 + bonus))`
+///
+/// The bonus is set by the asset bank's `LiquidationMode`. In auction mode it rises from zero
+/// since the liquidatee's auction started, see `lending_account_update_liquidation_auction`,
+/// and the auction is reset once a liquidation brings the liquidatee back to health.
+///
+/// Only accounts under their maintenance requirement can be liquidated, and at most the liab
+/// bank's close factor of the liability is repaid at once. The liquidatee may end up at most
+/// `LIQUIDATION_MAX_HEALTH_BUFFER_BPS` above its maintenance requirement.
 ///
 /// Expected remaining account schema
 /// [
-///    asset_oracle_ai,
-///    liab_oracle_ai,
+///    ...asset_oracle_ais,
+///    ...liab_oracle_ais,
+///    ...liquidatee_health_ais,
 ///  ]
+///
+/// A bank's oracle accounts are a single feed, or `RAYDIUM_LP_ORACLE_AIS_LEN` accounts
+/// for Raydium LP banks, see `fetch_bank_price`. The liquidatee health accounts are laid out
+/// as in `RiskEngine::calc_account_health`.
 pub fn lending_account_liquidate_process<'info>(
     ctx: Context<'_, '_, 'info, 'info, LendingAccountLiquidate<'info>>,
     asset_amount: u64
@@ -36,179 +51,311 @@ pub fn lending_account_liquidate_process<'info>(
     let clock = Clock::get().map_err(|_| OrbitlenError::GetClockFailed)?;
     let current_timestamp = clock.unix_timestamp;
     {
-        ctx.accounts.asset_bank.load_mut()?.accrue_interest(current_timestamp)?;
-        ctx.accounts.liab_bank.load_mut()?.accrue_interest(current_timestamp)?;
+        ctx.accounts.asset_bank
+            .load_mut()?
+            .accrue_interest(current_timestamp, &ctx.accounts.asset_bank.key())?;
+        ctx.accounts.liab_bank
+            .load_mut()?
+            .accrue_interest(current_timestamp, &ctx.accounts.liab_bank.key())?;
     }
 
+    let (asset_oracle_ais, liab_oracle_ais, liquidatee_health_ais) = split_liquidation_ais(
+        &*ctx.accounts.asset_bank.load()?,
+        &*ctx.accounts.liab_bank.load()?,
+        ctx.remaining_accounts
+    )?;
+
+    let pre_health = RiskEngine::calc_account_health(
+        &liquidatee_orbitlen_account,
+        liquidatee_health_ais,
+        RiskRequirementType::Maintenance
+    )?;
+    require!(!pre_health.is_healthy(), OrbitlenError::HealthyAccount);
+
+    let auction_elapsed_slots = liquidatee_orbitlen_account.start_liquidation_auction(clock.slot);
+
     let (pre_balances, post_balances) = {
         let mut asset_bank = ctx.accounts.asset_bank.load_mut()?;
+        let mut liab_bank = ctx.accounts.liab_bank.load_mut()?;
 
-        let asset_price = {
-            let oracle_ais = &ctx.remaining_accounts[0..1];
-            fetch_feed_price(&oracle_ais[0], &asset_bank.config)?
-        };
-        // WIF / USD 2.8
-        // let asset_price = 2.8;
-        msg!("asset_price: {}", asset_price);
+        // Quantity of liability to be paid off by liquidator and received by liquidatee
+        let liab_amount = calc_liquidation_liab_amount(
+            &asset_bank,
+            &liab_bank,
+            asset_amount,
+            asset_bank.config.get_liquidation_bonus_bps(auction_elapsed_slots),
+            asset_oracle_ais,
+            liab_oracle_ais
+        )?;
 
-        let mut liab_bank = ctx.accounts.liab_bank.load_mut()?;
-        let liab_price = {
-            let oracle_ais = &ctx.remaining_accounts[1..2];
-            fetch_feed_price(&oracle_ais[0], &liab_bank.config)?
-        };
+        check_close_factor(
+            &liab_bank,
+            &ctx.accounts.liab_bank.key(),
+            &liquidatee_orbitlen_account.lending_account,
+            liab_amount
+        )?;
 
-        // let liab_price = 250.0;
-        // AAPL / USD 250
+        settle_liquidation_balances(
+            &ctx.accounts.asset_bank.key(),
+            &mut asset_bank,
+            &ctx.accounts.liab_bank.key(),
+            &mut liab_bank,
+            &mut liquidator_orbitlen_account.lending_account,
+            &mut liquidatee_orbitlen_account.lending_account,
+            asset_amount,
+            liab_amount
+        )?
+    };
 
-        msg!("liab_price: {}", liab_price);
+    let post_health = RiskEngine::calc_account_health(
+        &liquidatee_orbitlen_account,
+        liquidatee_health_ais,
+        RiskRequirementType::Maintenance
+    )?;
+    require!(
+        !post_health.exceeds_buffer(LIQUIDATION_MAX_HEALTH_BUFFER_BPS),
+        OrbitlenError::LiquidationOvershoot
+    );
 
-        // Quantity of liability to be paid off by liquidator and received by liquidatee
-        let liab_amount = calc_amount(
-            calc_value(asset_amount, asset_price, asset_bank.mint_decimals)?,
-            liab_price,
-            liab_bank.mint_decimals
+    if post_health.is_healthy() {
+        liquidatee_orbitlen_account.reset_liquidation_auction();
+    }
+
+    emit!(LendingAccountLiquidateEvent {
+        header: AccountEventHeader {
+            signer: ctx.accounts.signer.key(),
+            orbitlen_account: liquidator_orbitlen_account_loader.key(),
+            orbitlen_account_authority: liquidator_orbitlen_account.authority,
+        },
+        liquidatee_orbitlen_account: liquidatee_orbitlen_account_loader.key(),
+        liquidatee_orbitlen_account_authority: liquidatee_orbitlen_account.authority,
+        asset_bank: ctx.accounts.asset_bank.key(),
+        asset_mint: ctx.accounts.asset_bank.load_mut()?.mint,
+        liability_bank: ctx.accounts.liab_bank.key(),
+        liability_mint: ctx.accounts.liab_bank.load_mut()?.mint,
+        pre_balances,
+        post_balances,
+    });
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+/// Moves `liab_amount` of the liability from the liquidatee to the liquidator and `asset_amount`
+/// of collateral the other way, returning the balances before and after
+pub fn settle_liquidation_balances(
+    asset_bank_pk: &Pubkey,
+    asset_bank: &mut Bank,
+    liab_bank_pk: &Pubkey,
+    liab_bank: &mut Bank,
+    liquidator_lending_account: &mut LendingAccount,
+    liquidatee_lending_account: &mut LendingAccount,
+    asset_amount: u64,
+    liab_amount: u64
+) -> Result<(LiquidationBalances, LiquidationBalances)> {
+    // Liquidator pays off liability
+    let (liquidator_liability_pre_balance, liquidator_liability_post_balance) = {
+        let mut bank_account = BankAccountWrapper::find_or_create(
+            liab_bank_pk,
+            liab_bank,
+            liquidator_lending_account
         )?;
 
-        msg!("liab_amount: {}, asset_amount: {}", liab_amount, asset_amount);
+        let pre_balance = bank_account.bank.get_liability_amount(
+            bank_account.balance.liability_shares.into()
+        )?;
 
-        // Liquidator pays off liability
-        let (liquidator_liability_pre_balance, liquidator_liability_post_balance) = {
-            let mut bank_account = BankAccountWrapper::find_or_create(
-                &ctx.accounts.liab_bank.key(),
-                &mut liab_bank,
-                &mut liquidator_orbitlen_account.lending_account
-            )?;
+        bank_account.decrease_balance_in_liquidation(liab_amount)?;
 
-            let pre_balance = bank_account.bank.get_liability_amount(
-                bank_account.balance.liability_shares.into()
-            )?;
+        let post_balance = bank_account.bank.get_liability_amount(
+            bank_account.balance.liability_shares.into()
+        )?;
 
-            bank_account.decrease_balance_in_liquidation(liab_amount)?;
+        (pre_balance, post_balance)
+    };
 
-            let post_balance = bank_account.bank.get_liability_amount(
-                bank_account.balance.liability_shares.into()
-            )?;
+    debug!(
+        "liquidator_liability_pre_balance: {}, liquidator_liability_post_balance: {}",
+        liquidator_liability_pre_balance,
+        liquidator_liability_post_balance
+    );
 
-            (pre_balance, post_balance)
-        };
+    // Liquidatee pays off `asset_quantity` amount of collateral
+    let (liquidatee_asset_pre_balance, liquidatee_asset_post_balance) = {
+        let mut bank_account = BankAccountWrapper::find(
+            asset_bank_pk,
+            asset_bank,
+            liquidatee_lending_account
+        )?;
 
-        msg!(
-            "liquidator_liability_pre_balance: {}, liquidator_liability_post_balance: {}",
-            liquidator_liability_pre_balance,
-            liquidator_liability_post_balance
-        );
+        let pre_balance = bank_account.bank.get_asset_amount(
+            bank_account.balance.asset_shares.into()
+        )?;
 
-        // Liquidatee pays off `asset_quantity` amount of collateral
-        let (liquidatee_asset_pre_balance, liquidatee_asset_post_balance) = {
-            let mut bank_account = BankAccountWrapper::find(
-                &ctx.accounts.asset_bank.key(),
-                &mut asset_bank,
-                &mut liquidatee_orbitlen_account.lending_account
-            )?;
+        bank_account.withdraw(asset_amount)?;
 
-            let pre_balance = bank_account.bank.get_asset_amount(
-                bank_account.balance.asset_shares.into()
-            )?;
+        let post_balance = bank_account.bank.get_asset_amount(
+            bank_account.balance.asset_shares.into()
+        )?;
 
-            bank_account.withdraw(asset_amount)?;
+        (pre_balance, post_balance)
+    };
 
-            let post_balance = bank_account.bank.get_asset_amount(
-                bank_account.balance.asset_shares.into()
-            )?;
+    debug!(
+        "liquidatee_asset_pre_balance: {}, liquidatee_asset_post_balance: {}",
+        liquidatee_asset_pre_balance,
+        liquidatee_asset_post_balance
+    );
 
-            (pre_balance, post_balance)
-        };
+    // Liquidator receives `asset_quantity` amount of collateral
+    let (liquidator_asset_pre_balance, liquidator_asset_post_balance) = {
+        let mut bank_account = BankAccountWrapper::find_or_create(
+            asset_bank_pk,
+            asset_bank,
+            liquidator_lending_account
+        )?;
 
-        msg!(
-            "liquidatee_asset_pre_balance: {}, liquidatee_asset_post_balance: {}",
-            liquidatee_asset_pre_balance,
-            liquidatee_asset_post_balance
-        );
+        let pre_balance = bank_account.bank.get_asset_amount(
+            bank_account.balance.asset_shares.into()
+        )?;
 
-        // Liquidator receives `asset_quantity` amount of collateral
-        let (liquidator_asset_pre_balance, liquidator_asset_post_balance) = {
-            let mut bank_account = BankAccountWrapper::find_or_create(
-                &ctx.accounts.asset_bank.key(),
-                &mut asset_bank,
-                &mut liquidator_orbitlen_account.lending_account
-            )?;
+        bank_account.increase_balance_in_liquidation(asset_amount)?;
 
-            let pre_balance = bank_account.bank.get_asset_amount(
-                bank_account.balance.asset_shares.into()
-            )?;
+        let post_balance = bank_account.bank.get_asset_amount(
+            bank_account.balance.asset_shares.into()
+        )?;
 
-            bank_account.increase_balance_in_liquidation(asset_amount)?;
+        (pre_balance, post_balance)
+    };
+
+    debug!(
+        "liquidator_asset_pre_balance: {}, liquidator_asset_post_balance: {}",
+        liquidator_asset_pre_balance,
+        liquidator_asset_post_balance
+    );
+    // Liquidatee receives liability payment
+    let (liquidatee_liability_pre_balance, liquidatee_liability_post_balance) = {
+        let mut liquidatee_liab_bank_account = BankAccountWrapper::find_or_create(
+            liab_bank_pk,
+            liab_bank,
+            liquidatee_lending_account
+        )?;
 
-            let post_balance = bank_account.bank.get_asset_amount(
-                bank_account.balance.asset_shares.into()
+        let liquidatee_liability_pre_balance =
+            liquidatee_liab_bank_account.bank.get_liability_amount(
+                liquidatee_liab_bank_account.balance.liability_shares.into()
             )?;
 
-            (pre_balance, post_balance)
-        };
+        liquidatee_liab_bank_account.increase_balance(liab_amount)?;
 
-        msg!(
-            "liquidator_asset_pre_balance: {}, liquidator_asset_post_balance: {}",
-            liquidator_asset_pre_balance,
-            liquidator_asset_post_balance
-        );
-        // Liquidatee receives liability payment
-        let (liquidatee_liability_pre_balance, liquidatee_liability_post_balance) = {
-            let mut liquidatee_liab_bank_account = BankAccountWrapper::find_or_create(
-                &ctx.accounts.liab_bank.key(),
-                &mut liab_bank,
-                &mut liquidatee_orbitlen_account.lending_account
+        let liquidatee_liability_post_balance =
+            liquidatee_liab_bank_account.bank.get_liability_amount(
+                liquidatee_liab_bank_account.balance.liability_shares.into()
             )?;
+        (liquidatee_liability_pre_balance, liquidatee_liability_post_balance)
+    };
+    debug!(
+        "liquidatee_liability_pre_balance: {}, liquidatee_liability_post_balance: {}",
+        liquidatee_liability_pre_balance,
+        liquidatee_liability_post_balance
+    );
 
-            let liquidatee_liability_pre_balance =
-                liquidatee_liab_bank_account.bank.get_liability_amount(
-                    liquidatee_liab_bank_account.balance.liability_shares.into()
-                )?;
+    Ok((
+        LiquidationBalances {
+            liquidatee_asset_balance: liquidatee_asset_pre_balance as f64,
+            liquidatee_liability_balance: liquidatee_liability_pre_balance as f64,
+            liquidator_asset_balance: liquidator_asset_pre_balance as f64,
+            liquidator_liability_balance: liquidator_liability_pre_balance as f64,
+        },
+        LiquidationBalances {
+            liquidatee_asset_balance: liquidatee_asset_post_balance as f64,
+            liquidatee_liability_balance: liquidatee_liability_post_balance as f64,
+            liquidator_asset_balance: liquidator_asset_post_balance as f64,
+            liquidator_liability_balance: liquidator_liability_post_balance as f64,
+        },
+    ))
+}
+
+/// Splits the liquidation remaining accounts into the asset oracle, liab oracle and liquidatee
+/// health accounts, the latter running to the end of `remaining_ais`
+pub fn split_liquidation_ais<'info>(
+    asset_bank: &Bank,
+    liab_bank: &Bank,
+    remaining_ais: &'info [AccountInfo<'info>]
+) -> Result<(&'info [AccountInfo<'info>], &'info [AccountInfo<'info>], &'info [AccountInfo<'info>])> {
+    let asset_oracle_ais_len = asset_bank.config.get_oracle_ais_len();
+    let liab_oracle_ais_len = liab_bank.config.get_oracle_ais_len();
+
+    require_gte!(
+        remaining_ais.len(),
+        asset_oracle_ais_len + liab_oracle_ais_len,
+        OrbitlenError::MissingOracleAccounts
+    );
 
-            liquidatee_liab_bank_account.increase_balance(liab_amount)?;
+    let (asset_oracle_ais, rest) = remaining_ais.split_at(asset_oracle_ais_len);
+    let (liab_oracle_ais, liquidatee_health_ais) = rest.split_at(liab_oracle_ais_len);
 
-            let liquidatee_liability_post_balance =
-                liquidatee_liab_bank_account.bank.get_liability_amount(
-                    liquidatee_liab_bank_account.balance.liability_shares.into()
-                )?;
-            (liquidatee_liability_pre_balance, liquidatee_liability_post_balance)
-        };
-        msg!(
-            "liquidatee_liability_pre_balance: {}, liquidatee_liability_post_balance: {}",
-            liquidatee_liability_pre_balance,
-            liquidatee_liability_post_balance
-        );
+    Ok((asset_oracle_ais, liab_oracle_ais, liquidatee_health_ais))
+}
 
-        (
-            LiquidationBalances {
-                liquidatee_asset_balance: liquidatee_asset_pre_balance as f64,
-                liquidatee_liability_balance: liquidatee_liability_pre_balance as f64,
-                liquidator_asset_balance: liquidator_asset_pre_balance as f64,
-                liquidator_liability_balance: liquidator_liability_pre_balance as f64,
-            },
-            LiquidationBalances {
-                liquidatee_asset_balance: liquidatee_asset_post_balance as f64,
-                liquidatee_liability_balance: liquidatee_liability_post_balance as f64,
-                liquidator_asset_balance: liquidator_asset_post_balance as f64,
-                liquidator_liability_balance: liquidator_liability_post_balance as f64,
-            },
+/// Quantity of liability worth `asset_amount` of collateral at the current oracle prices, less
+/// the liquidator's `bonus_bps`
+pub fn calc_liquidation_liab_amount(
+    asset_bank: &Bank,
+    liab_bank: &Bank,
+    asset_amount: u64,
+    bonus_bps: u32,
+    asset_oracle_ais: &[AccountInfo],
+    liab_oracle_ais: &[AccountInfo]
+) -> Result<u64> {
+    let asset_price = fetch_bank_price(asset_bank, asset_oracle_ais)?;
+    debug!("asset_price: {}", asset_price);
+
+    let liab_price = fetch_bank_price(liab_bank, liab_oracle_ais)?;
+    debug!("liab_price: {}", liab_price);
+
+    let liab_amount = math
+        ::liquidation_liab_amount(
+            asset_amount,
+            asset_price,
+            asset_bank.mint_decimals,
+            liab_price,
+            liab_bank.mint_decimals,
+            bonus_bps
         )
-    };
+        .ok_or(OrbitlenError::MathError)?;
 
-    emit!(LendingAccountLiquidateEvent {
-        header: AccountEventHeader {
-            signer: ctx.accounts.signer.key(),
-            orbitlen_account: liquidator_orbitlen_account_loader.key(),
-            orbitlen_account_authority: liquidator_orbitlen_account.authority,
-        },
-        liquidatee_orbitlen_account: liquidatee_orbitlen_account_loader.key(),
-        liquidatee_orbitlen_account_authority: liquidatee_orbitlen_account.authority,
-        asset_bank: ctx.accounts.asset_bank.key(),
-        asset_mint: ctx.accounts.asset_bank.load_mut()?.mint,
-        liability_bank: ctx.accounts.liab_bank.key(),
-        liability_mint: ctx.accounts.liab_bank.load_mut()?.mint,
-        pre_balances,
-        post_balances,
-    });
+    debug!(
+        "liab_amount: {}, asset_amount: {}, bonus_bps: {}",
+        liab_amount,
+        asset_amount,
+        bonus_bps
+    );
+
+    Ok(liab_amount)
+}
+
+/// At most the close factor of the liquidatee's liability can be repaid at once
+pub fn check_close_factor(
+    liab_bank: &Bank,
+    liab_bank_pk: &Pubkey,
+    liquidatee_lending_account: &LendingAccount,
+    liab_amount: u64
+) -> Result<()> {
+    let liability_shares = liquidatee_lending_account.balances
+        .iter()
+        .find(|balance| balance.bank_pk == *liab_bank_pk)
+        .map(|balance| balance.liability_shares)
+        .unwrap_or(0);
+    let liability_amount = liab_bank.get_liability_amount(liability_shares)?;
+    let max_liab_amount = math::close_factor_liab_amount(
+        liability_amount,
+        liab_bank.config.close_factor_bps
+    );
+
+    debug!("liability_amount: {}, max_liab_amount: {}", liability_amount, max_liab_amount);
+
+    require_gte!(max_liab_amount, liab_amount as u128, OrbitlenError::CloseFactorExceeded);
 
     Ok(())
 }
@@ -226,3 +373,71 @@ pub struct LendingAccountLiquidate<'info> {
     #[account(mut)]
     pub liquidatee_orbitlen_account: AccountLoader<'info, OrbitlenAccount>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    fn liab_bank(close_factor_bps: u16) -> Bank {
+        let config = BankConfig { close_factor_bps, ..Default::default() };
+
+        Bank::new(Pubkey::new_unique(), 6, config, 0, Pubkey::default(), 0, 0)
+    }
+
+    #[test]
+    fn liab_amount_with_prices_below_one() {
+        // 8 tokens at 0.25 repay 4 tokens at 0.5, the bonus comes off the repaid amount
+        assert_eq!(math::liquidation_liab_amount(8_000_000_000, 0.25, 9, 0.5, 6, 0), Some(4_000_000));
+        assert_eq!(
+            math::liquidation_liab_amount(8_000_000_000, 0.25, 9, 0.5, 6, 1_000),
+            Some(3_636_363)
+        );
+        assert_eq!(math::liquidation_liab_amount(8_000_000_000, 0.25, 9, 0.0, 6, 0), None);
+    }
+
+    #[test]
+    fn liab_amount_with_mixed_decimals() {
+        // 2 SOL at 150 against USDC at 1, and back
+        let usdc_amount = math::liquidation_liab_amount(2_000_000_000, 150.0, 9, 1.0, 6, 0);
+        assert_eq!(usdc_amount, Some(300_000_000));
+        assert_eq!(
+            math::liquidation_liab_amount(300_000_000, 1.0, 6, 150.0, 9, 0),
+            Some(2_000_000_000)
+        );
+        assert_eq!(
+            math::token_value(usdc_amount.unwrap(), 6, 1.0),
+            math::token_value(2_000_000_000, 9, 150.0)
+        );
+    }
+
+    #[test]
+    fn close_factor_caps_repaid_liability() {
+        let liab_bank_pk = Pubkey::new_unique();
+        let liab_bank = liab_bank(5_000);
+
+        let mut lending_account = OrbitlenAccount::zeroed().lending_account;
+        lending_account.balances[0].bank_pk = liab_bank_pk;
+        lending_account.balances[0].liability_shares = 1_000_000;
+
+        assert!(check_close_factor(&liab_bank, &liab_bank_pk, &lending_account, 500_000).is_ok());
+        assert!(check_close_factor(&liab_bank, &liab_bank_pk, &lending_account, 500_001).is_err());
+        // Nothing can be repaid to a bank the liquidatee doesn't owe
+        assert!(
+            check_close_factor(&liab_bank, &Pubkey::new_unique(), &lending_account, 1).is_err()
+        );
+    }
+
+    #[test]
+    fn liquidation_may_not_overshoot_health_buffer() {
+        let at_buffer = AccountHealth { assets: 105.0, liabilities: 100.0, ..Default::default() };
+        let above_buffer = AccountHealth {
+            assets: 105.5,
+            liabilities: 100.0,
+            ..Default::default()
+        };
+
+        assert!(!at_buffer.exceeds_buffer(LIQUIDATION_MAX_HEALTH_BUFFER_BPS));
+        assert!(above_buffer.exceeds_buffer(LIQUIDATION_MAX_HEALTH_BUFFER_BPS));
+    }
+}
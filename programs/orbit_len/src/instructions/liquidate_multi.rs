@@ -0,0 +1,299 @@
+use crate::constants::*;
+use crate::error::OrbitlenError;
+use crate::events::*;
+use crate::state::*;
+use crate::instructions::liquidate::*;
+use anchor_lang::prelude::*;
+use solana_program::clock::Clock;
+use solana_program::sysvar::Sysvar;
+
+/// Asset / liab bank pair of a multi-leg liquidation, read from the remaining accounts
+struct LiquidationLeg<'info> {
+    asset_bank: AccountLoader<'info, Bank>,
+    asset_oracle_ais: &'info [AccountInfo<'info>],
+    liab_bank: AccountLoader<'info, Bank>,
+    liab_oracle_ais: &'info [AccountInfo<'info>],
+}
+
+/// `lending_account_liquidate` over several asset / liab bank pairs in one go, so an account
+/// with many small positions can be cleaned up atomically. Leg `i` seizes `asset_amounts[i]` of
+/// collateral and is priced and settled like a single liquidation. The close factor caps what
+/// all legs repay to a liab bank together, and the liquidatee's health is checked once before
+/// and once after all legs.
+///
+/// Expected remaining account schema
+/// [
+///    ...for each leg [asset_bank_ai, ...asset_oracle_ais, liab_bank_ai, ...liab_oracle_ais],
+///    ...liquidatee_health_ais,
+///  ]
+pub fn lending_account_liquidate_multi_process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, LendingAccountLiquidateMulti<'info>>,
+    asset_amounts: Vec<u64>
+) -> Result<()> {
+    require!(!asset_amounts.is_empty(), OrbitlenError::IllegalLiquidation);
+
+    let LendingAccountLiquidateMulti {
+        liquidator_orbitlen_account: liquidator_orbitlen_account_loader,
+        liquidatee_orbitlen_account: liquidatee_orbitlen_account_loader,
+        signer,
+    } = ctx.accounts;
+
+    let mut liquidator_orbitlen_account = liquidator_orbitlen_account_loader.load_mut()?;
+    let mut liquidatee_orbitlen_account = liquidatee_orbitlen_account_loader.load_mut()?;
+    let clock = Clock::get().map_err(|_| OrbitlenError::GetClockFailed)?;
+
+    let mut legs: Vec<LiquidationLeg> = Vec::with_capacity(asset_amounts.len());
+    let mut ais: &'info [AccountInfo<'info>] = ctx.remaining_accounts;
+
+    for asset_amount in asset_amounts.iter() {
+        require_gt!(*asset_amount, 0, OrbitlenError::IllegalLiquidation);
+
+        let (asset_bank, asset_oracle_ais, rest) = take_bank_ais(ais)?;
+        let (liab_bank, liab_oracle_ais, rest) = take_bank_ais(rest)?;
+        ais = rest;
+
+        require_keys_neq!(asset_bank.key(), liab_bank.key(), OrbitlenError::IllegalLiquidation);
+
+        asset_bank.load_mut()?.accrue_interest(clock.unix_timestamp, &asset_bank.key())?;
+        liab_bank.load_mut()?.accrue_interest(clock.unix_timestamp, &liab_bank.key())?;
+
+        legs.push(LiquidationLeg { asset_bank, asset_oracle_ais, liab_bank, liab_oracle_ais });
+    }
+
+    let liquidatee_health_ais = ais;
+
+    let pre_health = RiskEngine::calc_account_health(
+        &liquidatee_orbitlen_account,
+        liquidatee_health_ais,
+        RiskRequirementType::Maintenance
+    )?;
+    require!(!pre_health.is_healthy(), OrbitlenError::HealthyAccount);
+
+    let auction_elapsed_slots = liquidatee_orbitlen_account.start_liquidation_auction(clock.slot);
+
+    // The close factor applies to the liability the liquidatee held before any leg
+    let pre_liquidation_lending_account = liquidatee_orbitlen_account.lending_account;
+    let mut repaid_liab_amounts: Vec<(Pubkey, u64)> = Vec::with_capacity(legs.len());
+
+    for (leg, asset_amount) in legs.iter().zip(asset_amounts) {
+        let mut asset_bank = leg.asset_bank.load_mut()?;
+        let mut liab_bank = leg.liab_bank.load_mut()?;
+
+        // Quantity of liability to be paid off by liquidator and received by liquidatee
+        let liab_amount = calc_liquidation_liab_amount(
+            &asset_bank,
+            &liab_bank,
+            asset_amount,
+            asset_bank.config.get_liquidation_bonus_bps(auction_elapsed_slots),
+            leg.asset_oracle_ais,
+            leg.liab_oracle_ais
+        )?;
+
+        let repaid_liab_amount = add_repaid_liab_amount(
+            &mut repaid_liab_amounts,
+            leg.liab_bank.key(),
+            liab_amount
+        )?;
+
+        check_close_factor(
+            &liab_bank,
+            &leg.liab_bank.key(),
+            &pre_liquidation_lending_account,
+            repaid_liab_amount
+        )?;
+
+        let (pre_balances, post_balances) = settle_liquidation_balances(
+            &leg.asset_bank.key(),
+            &mut asset_bank,
+            &leg.liab_bank.key(),
+            &mut liab_bank,
+            &mut liquidator_orbitlen_account.lending_account,
+            &mut liquidatee_orbitlen_account.lending_account,
+            asset_amount,
+            liab_amount
+        )?;
+
+        emit!(LendingAccountLiquidateEvent {
+            header: AccountEventHeader {
+                signer: signer.key(),
+                orbitlen_account: liquidator_orbitlen_account_loader.key(),
+                orbitlen_account_authority: liquidator_orbitlen_account.authority,
+            },
+            liquidatee_orbitlen_account: liquidatee_orbitlen_account_loader.key(),
+            liquidatee_orbitlen_account_authority: liquidatee_orbitlen_account.authority,
+            asset_bank: leg.asset_bank.key(),
+            asset_mint: asset_bank.mint,
+            liability_bank: leg.liab_bank.key(),
+            liability_mint: liab_bank.mint,
+            pre_balances,
+            post_balances,
+        });
+    }
+
+    let post_health = RiskEngine::calc_account_health(
+        &liquidatee_orbitlen_account,
+        liquidatee_health_ais,
+        RiskRequirementType::Maintenance
+    )?;
+    require!(
+        !post_health.exceeds_buffer(LIQUIDATION_MAX_HEALTH_BUFFER_BPS),
+        OrbitlenError::LiquidationOvershoot
+    );
+
+    if post_health.is_healthy() {
+        liquidatee_orbitlen_account.reset_liquidation_auction();
+    }
+
+    Ok(())
+}
+
+/// Adds `liab_amount` to what earlier legs repaid to `liab_bank_pk` and returns the total,
+/// which the close factor caps
+fn add_repaid_liab_amount(
+    repaid_liab_amounts: &mut Vec<(Pubkey, u64)>,
+    liab_bank_pk: Pubkey,
+    liab_amount: u64
+) -> Result<u64> {
+    match repaid_liab_amounts.iter_mut().find(|(bank_pk, _)| *bank_pk == liab_bank_pk) {
+        Some((_, repaid_liab_amount)) => {
+            *repaid_liab_amount = repaid_liab_amount
+                .checked_add(liab_amount)
+                .ok_or(OrbitlenError::MathError)?;
+            Ok(*repaid_liab_amount)
+        }
+        None => {
+            repaid_liab_amounts.push((liab_bank_pk, liab_amount));
+            Ok(liab_amount)
+        }
+    }
+}
+
+/// Takes a bank and its oracle accounts off the front of `ais`
+fn take_bank_ais<'info>(
+    ais: &'info [AccountInfo<'info>]
+) -> Result<(AccountLoader<'info, Bank>, &'info [AccountInfo<'info>], &'info [AccountInfo<'info>])> {
+    let (bank_ai, rest) = ais.split_first().ok_or(OrbitlenError::MissingOracleAccounts)?;
+    let bank = AccountLoader::<Bank>::try_from(bank_ai)?;
+
+    let oracle_ais_len = bank.load()?.config.get_oracle_ais_len();
+    require_gte!(rest.len(), oracle_ais_len, OrbitlenError::MissingOracleAccounts);
+    let (oracle_ais, rest) = rest.split_at(oracle_ais_len);
+
+    Ok((bank, oracle_ais, rest))
+}
+
+#[derive(Accounts)]
+pub struct LendingAccountLiquidateMulti<'info> {
+    #[account(mut)]
+    pub liquidator_orbitlen_account: AccountLoader<'info, OrbitlenAccount>,
+    #[account(address = liquidator_orbitlen_account.load()?.authority)]
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub liquidatee_orbitlen_account: AccountLoader<'info, OrbitlenAccount>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    fn bank(close_factor_bps: u16) -> Bank {
+        let config = BankConfig { close_factor_bps, ..Default::default() };
+
+        Bank::new(Pubkey::new_unique(), 6, config, 0, Pubkey::default(), 0, 0)
+    }
+
+    fn set_balance(
+        lending_account: &mut LendingAccount,
+        index: usize,
+        bank_pk: Pubkey,
+        asset_shares: u64,
+        liability_shares: u64
+    ) {
+        lending_account.balances[index].bank_pk = bank_pk;
+        lending_account.balances[index].asset_shares = asset_shares;
+        lending_account.balances[index].liability_shares = liability_shares;
+    }
+
+    fn balance_shares(lending_account: &LendingAccount, bank_pk: &Pubkey) -> (u64, u64) {
+        let balance = lending_account.balances
+            .iter()
+            .find(|balance| balance.bank_pk == *bank_pk)
+            .unwrap();
+
+        (balance.asset_shares, balance.liability_shares)
+    }
+
+    #[test]
+    fn close_factor_caps_legs_repaying_the_same_liab_bank_together() {
+        let (liab_bank_pk, other_liab_bank_pk) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let liab_bank = bank(5_000);
+
+        let mut lending_account = OrbitlenAccount::zeroed().lending_account;
+        set_balance(&mut lending_account, 0, liab_bank_pk, 0, 1_000_000);
+        set_balance(&mut lending_account, 1, other_liab_bank_pk, 0, 1_000_000);
+
+        let mut repaid_liab_amounts = Vec::new();
+        let mut repay = |liab_bank_pk: Pubkey, liab_amount: u64| {
+            let repaid_liab_amount = add_repaid_liab_amount(
+                &mut repaid_liab_amounts,
+                liab_bank_pk,
+                liab_amount
+            ).unwrap();
+
+            check_close_factor(&liab_bank, &liab_bank_pk, &lending_account, repaid_liab_amount)
+                .map(|_| repaid_liab_amount)
+        };
+
+        // Each leg alone stays under half of the liability, the second one doesn't together
+        // with the first
+        assert_eq!(repay(liab_bank_pk, 300_000), Ok(300_000));
+        assert_eq!(repay(other_liab_bank_pk, 300_000), Ok(300_000));
+        assert_eq!(repay(other_liab_bank_pk, 200_000), Ok(500_000));
+        assert!(repay(liab_bank_pk, 300_000).is_err());
+    }
+
+    #[test]
+    fn legs_sharing_banks_settle_one_after_another() {
+        let (asset_bank_pk, liab_bank_pk) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut asset_bank = bank(BASIS_POINTS as u16);
+        let mut liab_bank = bank(BASIS_POINTS as u16);
+        asset_bank.total_asset_shares = 1_000;
+        // Other depositors keep the liab bank liquid once the liquidator withdraws
+        liab_bank.total_asset_shares = 2_000;
+        liab_bank.total_liability_shares = 1_000;
+
+        let mut liquidator = OrbitlenAccount::zeroed().lending_account;
+        set_balance(&mut liquidator, 0, liab_bank_pk, 1_000, 0);
+        // Opening a balance reads the clock sysvar, which tests don't have
+        set_balance(&mut liquidator, 1, asset_bank_pk, 0, 0);
+        let mut liquidatee = OrbitlenAccount::zeroed().lending_account;
+        set_balance(&mut liquidatee, 0, asset_bank_pk, 1_000, 0);
+        set_balance(&mut liquidatee, 1, liab_bank_pk, 0, 1_000);
+
+        // Two legs over the same asset / liab bank pair
+        for _ in 0..2 {
+            settle_liquidation_balances(
+                &asset_bank_pk,
+                &mut asset_bank,
+                &liab_bank_pk,
+                &mut liab_bank,
+                &mut liquidator,
+                &mut liquidatee,
+                100,
+                100
+            ).unwrap();
+        }
+
+        assert_eq!(balance_shares(&liquidatee, &asset_bank_pk), (800, 0));
+        assert_eq!(balance_shares(&liquidatee, &liab_bank_pk), (0, 800));
+        assert_eq!(balance_shares(&liquidator, &asset_bank_pk), (200, 0));
+        assert_eq!(balance_shares(&liquidator, &liab_bank_pk), (800, 0));
+        let (asset_bank_shares, liab_bank_shares) = (
+            asset_bank.total_asset_shares,
+            liab_bank.total_liability_shares,
+        );
+        assert_eq!(asset_bank_shares, 1_000);
+        assert_eq!(liab_bank_shares, 800);
+    }
+}
@@ -0,0 +1,293 @@
+use crate::{ constants::*, error::OrbitlenError, events::*, state::*, utils };
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenInterface };
+use solana_program::{ clock::Clock, sysvar::Sysvar };
+use crate::instructions::liquidate::*;
+
+/// Same pricing, bonus, close factor and liquidatee health target as `lending_account_liquidate`,
+/// but settled in tokens so the liquidator needs no `OrbitlenAccount`: the liability is repaid
+/// from the liquidator's wallet into the liab bank vault, and the seized collateral is paid out
+/// of the asset bank vault. The liquidator covers any Token-2022 transfer fee of the liability
+/// mint.
+///
+/// Expected remaining account schema
+/// [
+///    ...asset_oracle_ais,
+///    ...liab_oracle_ais,
+///    ...liquidatee_health_ais,
+///    ...transfer_hook_extra_ais,
+///  ]
+pub fn lending_account_liquidate_with_tokens_process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, LendingAccountLiquidateWithTokens<'info>>,
+    asset_amount: u64
+) -> Result<()> {
+    require_gt!(asset_amount, 0, OrbitlenError::IllegalLiquidation);
+
+    require_keys_neq!(
+        ctx.accounts.asset_bank.key(),
+        ctx.accounts.liab_bank.key(),
+        OrbitlenError::IllegalLiquidation
+    );
+
+    let LendingAccountLiquidateWithTokens {
+        liquidator,
+        asset_bank: asset_bank_loader,
+        asset_mint,
+        asset_bank_liquidity_vault,
+        asset_bank_liquidity_vault_authority,
+        liquidator_asset_token_account,
+        asset_token_program,
+        liab_bank: liab_bank_loader,
+        liab_mint,
+        liab_bank_liquidity_vault,
+        liquidator_liab_token_account,
+        liab_token_program,
+        liquidatee_orbitlen_account: liquidatee_orbitlen_account_loader,
+    } = ctx.accounts;
+
+    let mut liquidatee_orbitlen_account = liquidatee_orbitlen_account_loader.load_mut()?;
+    let clock = Clock::get().map_err(|_| OrbitlenError::GetClockFailed)?;
+    {
+        asset_bank_loader
+            .load_mut()?
+            .accrue_interest(clock.unix_timestamp, &asset_bank_loader.key())?;
+        liab_bank_loader
+            .load_mut()?
+            .accrue_interest(clock.unix_timestamp, &liab_bank_loader.key())?;
+    }
+
+    let (asset_oracle_ais, liab_oracle_ais, rest) = split_liquidation_ais(
+        &*asset_bank_loader.load()?,
+        &*liab_bank_loader.load()?,
+        ctx.remaining_accounts
+    )?;
+    let (liquidatee_health_ais, transfer_hook_extra_ais) = RiskEngine::split_health_ais(
+        &liquidatee_orbitlen_account,
+        &[],
+        rest
+    )?;
+
+    let pre_health = RiskEngine::calc_account_health(
+        &liquidatee_orbitlen_account,
+        liquidatee_health_ais,
+        RiskRequirementType::Maintenance
+    )?;
+    require!(!pre_health.is_healthy(), OrbitlenError::HealthyAccount);
+
+    let auction_elapsed_slots = liquidatee_orbitlen_account.start_liquidation_auction(clock.slot);
+
+    let (liab_amount, pre_balances, post_balances) = {
+        let mut asset_bank = asset_bank_loader.load_mut()?;
+        let mut liab_bank = liab_bank_loader.load_mut()?;
+
+        // Quantity of liability to be paid off by liquidator on behalf of the liquidatee
+        let liab_amount = calc_liquidation_liab_amount(
+            &asset_bank,
+            &liab_bank,
+            asset_amount,
+            asset_bank.config.get_liquidation_bonus_bps(auction_elapsed_slots),
+            asset_oracle_ais,
+            liab_oracle_ais
+        )?;
+
+        check_close_factor(
+            &liab_bank,
+            &liab_bank_loader.key(),
+            &liquidatee_orbitlen_account.lending_account,
+            liab_amount
+        )?;
+
+        // Liquidator pays off liability, grossed up so the vault receives `liab_amount`
+        let (liquidatee_liability_pre_balance, liquidatee_liability_post_balance) = {
+            let mut bank_account = BankAccountWrapper::find(
+                &liab_bank_loader.key(),
+                &mut liab_bank,
+                &mut liquidatee_orbitlen_account.lending_account
+            )?;
+
+            let pre_balance = bank_account.bank.get_liability_amount(
+                bank_account.balance.liability_shares
+            )?;
+
+            bank_account.increase_balance(liab_amount)?;
+
+            let post_balance = bank_account.bank.get_liability_amount(
+                bank_account.balance.liability_shares
+            )?;
+
+            let liab_amount_pre_fee = utils::calculate_pre_fee_spl_deposit_amount(
+                liab_mint.to_account_info(),
+                liab_amount,
+                clock.epoch
+            )?;
+
+            bank_account.deposit_spl_transfer(
+                liab_amount_pre_fee,
+                liquidator_liab_token_account.to_account_info(),
+                liab_bank_liquidity_vault.to_account_info(),
+                liquidator.to_account_info(),
+                liab_mint,
+                liab_token_program.to_account_info(),
+                transfer_hook_extra_ais
+            )?;
+
+            (pre_balance, post_balance)
+        };
+
+        debug!(
+            "liquidatee_liability_pre_balance: {}, liquidatee_liability_post_balance: {}",
+            liquidatee_liability_pre_balance,
+            liquidatee_liability_post_balance
+        );
+
+        // Liquidatee pays off `asset_amount` of collateral to the liquidator's wallet
+        let (liquidatee_asset_pre_balance, liquidatee_asset_post_balance) = {
+            let liquidity_vault_authority_bump = asset_bank.liquidity_vault_authority_bump;
+
+            let mut bank_account = BankAccountWrapper::find(
+                &asset_bank_loader.key(),
+                &mut asset_bank,
+                &mut liquidatee_orbitlen_account.lending_account
+            )?;
+
+            let pre_balance = bank_account.bank.get_asset_amount(
+                bank_account.balance.asset_shares
+            )?;
+
+            bank_account.withdraw(asset_amount)?;
+
+            let post_balance = bank_account.bank.get_asset_amount(
+                bank_account.balance.asset_shares
+            )?;
+
+            let signer_seeds: &[&[&[u8]]] = &[
+                &[
+                    BankVaultType::Liquidity.get_authority_seed(),
+                    &asset_bank_loader.key().to_bytes(),
+                    &[liquidity_vault_authority_bump],
+                ],
+            ];
+
+            bank_account.withdraw_spl_transfer(
+                asset_amount,
+                asset_bank_liquidity_vault.to_account_info(),
+                liquidator_asset_token_account.to_account_info(),
+                asset_bank_liquidity_vault_authority.to_account_info(),
+                asset_mint,
+                asset_token_program.to_account_info(),
+                signer_seeds,
+                transfer_hook_extra_ais
+            )?;
+
+            (pre_balance, post_balance)
+        };
+
+        debug!(
+            "liquidatee_asset_pre_balance: {}, liquidatee_asset_post_balance: {}",
+            liquidatee_asset_pre_balance,
+            liquidatee_asset_post_balance
+        );
+
+        (
+            liab_amount,
+            (liquidatee_asset_pre_balance, liquidatee_liability_pre_balance),
+            (liquidatee_asset_post_balance, liquidatee_liability_post_balance),
+        )
+    };
+
+    let post_health = RiskEngine::calc_account_health(
+        &liquidatee_orbitlen_account,
+        liquidatee_health_ais,
+        RiskRequirementType::Maintenance
+    )?;
+    require!(
+        !post_health.exceeds_buffer(LIQUIDATION_MAX_HEALTH_BUFFER_BPS),
+        OrbitlenError::LiquidationOvershoot
+    );
+
+    if post_health.is_healthy() {
+        liquidatee_orbitlen_account.reset_liquidation_auction();
+    }
+
+    emit!(LendingAccountLiquidateWithTokensEvent {
+        liquidator: liquidator.key(),
+        liquidatee_orbitlen_account: liquidatee_orbitlen_account_loader.key(),
+        liquidatee_orbitlen_account_authority: liquidatee_orbitlen_account.authority,
+        asset_bank: asset_bank_loader.key(),
+        asset_mint: asset_mint.key(),
+        liability_bank: liab_bank_loader.key(),
+        liability_mint: liab_mint.key(),
+        asset_amount,
+        liability_amount: liab_amount,
+        liquidatee_asset_pre_balance: pre_balances.0 as f64,
+        liquidatee_asset_post_balance: post_balances.0 as f64,
+        liquidatee_liability_pre_balance: pre_balances.1 as f64,
+        liquidatee_liability_post_balance: post_balances.1 as f64,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct LendingAccountLiquidateWithTokens<'info> {
+    pub liquidator: Signer<'info>,
+    /**
+     * Collateral leg, paid out to the liquidator
+     */
+    #[account(mut)]
+    pub asset_bank: AccountLoader<'info, Bank>,
+    #[account(
+        address = asset_bank.load()?.mint,
+        mint::token_program = asset_token_program,
+    )]
+    pub asset_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: Seed constraint check
+    #[account(
+        mut,
+        seeds = [
+            LIQUIDITY_VAULT_SEED.as_bytes(),
+            asset_bank.key().as_ref(),
+        ],
+        bump = asset_bank.load()?.liquidity_vault_bump,
+    )]
+    pub asset_bank_liquidity_vault: AccountInfo<'info>,
+    /// CHECK: Seed constraint check
+    #[account(
+        seeds = [
+            LIQUIDITY_VAULT_AUTHORITY_SEED.as_bytes(),
+            asset_bank.key().as_ref(),
+        ],
+        bump = asset_bank.load()?.liquidity_vault_authority_bump,
+    )]
+    pub asset_bank_liquidity_vault_authority: AccountInfo<'info>,
+    /// CHECK: Token mint is checked at transfer
+    #[account(mut)]
+    pub liquidator_asset_token_account: AccountInfo<'info>,
+    pub asset_token_program: Interface<'info, TokenInterface>,
+    /**
+     * Liability leg, repaid by the liquidator
+     */
+    #[account(mut)]
+    pub liab_bank: AccountLoader<'info, Bank>,
+    #[account(
+        address = liab_bank.load()?.mint,
+        mint::token_program = liab_token_program,
+    )]
+    pub liab_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: Seed constraint check
+    #[account(
+        mut,
+        seeds = [
+            LIQUIDITY_VAULT_SEED.as_bytes(),
+            liab_bank.key().as_ref(),
+        ],
+        bump = liab_bank.load()?.liquidity_vault_bump,
+    )]
+    pub liab_bank_liquidity_vault: AccountInfo<'info>,
+    /// CHECK: Token mint/authority are checked at transfer
+    #[account(mut)]
+    pub liquidator_liab_token_account: AccountInfo<'info>,
+    pub liab_token_program: Interface<'info, TokenInterface>,
+    #[account(mut)]
+    pub liquidatee_orbitlen_account: AccountLoader<'info, OrbitlenAccount>,
+}
@@ -0,0 +1,53 @@
+use crate::{ error::OrbitlenError, events::*, state::* };
+use anchor_lang::prelude::*;
+use solana_program::{ clock::Clock, sysvar::Sysvar };
+
+/// Permissionless crank starting the liquidation auction of an account under its maintenance
+/// requirement, or resetting it once the account is healthy again. Liquidations start the
+/// auction on their own as well, but the first one to do so always gets a zero bonus in
+/// auction mode, so keepers should crank this as soon as an account becomes liquidatable and
+/// again after every deposit, borrow or proxy call resetting the auction.
+///
+/// Expected remaining account schema, see `RiskEngine::calc_account_health`
+/// [
+///    ...health_ais,
+///  ]
+pub fn lending_account_update_liquidation_auction_process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, LendingAccountUpdateLiquidationAuction<'info>>
+) -> Result<()> {
+    let LendingAccountUpdateLiquidationAuction { orbitlen_account: orbitlen_account_loader, signer } =
+        ctx.accounts;
+
+    let clock = Clock::get().map_err(|_| OrbitlenError::GetClockFailed)?;
+    let mut orbitlen_account = orbitlen_account_loader.load_mut()?;
+
+    let health = RiskEngine::calc_account_health(
+        &orbitlen_account,
+        ctx.remaining_accounts,
+        RiskRequirementType::Maintenance
+    )?;
+
+    if health.is_healthy() {
+        orbitlen_account.reset_liquidation_auction();
+    } else {
+        orbitlen_account.start_liquidation_auction(clock.slot);
+    }
+
+    emit!(LendingAccountLiquidationAuctionEvent {
+        header: AccountEventHeader {
+            signer: signer.key(),
+            orbitlen_account: orbitlen_account_loader.key(),
+            orbitlen_account_authority: orbitlen_account.authority,
+        },
+        liquidation_auction_start_slot: orbitlen_account.liquidation_auction_start_slot,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct LendingAccountUpdateLiquidationAuction<'info> {
+    pub signer: Signer<'info>,
+    #[account(mut)]
+    pub orbitlen_account: AccountLoader<'info, OrbitlenAccount>,
+}
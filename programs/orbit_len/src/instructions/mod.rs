@@ -1,13 +1,31 @@
+pub mod accrue_interest;
 pub mod borrow;
+pub mod dex_adapter;
 pub mod deposit;
+pub mod emode;
+pub mod get_health;
 pub mod add_pool;
 pub mod initialize;
 pub mod liquidate;
+pub mod liquidate_multi;
+pub mod liquidate_with_tokens;
+pub mod liquidation_auction;
 pub mod raydium;
+pub mod raydium_cpmm;
+pub mod raydium_clmm;
 
+pub use accrue_interest::*;
 pub use borrow::*;
+pub use dex_adapter::*;
 pub use deposit::*;
+pub use emode::*;
+pub use get_health::*;
 pub use add_pool::*;
 pub use initialize::*;
 pub use liquidate::*;
-pub use raydium::*;
\ No newline at end of file
+pub use liquidate_multi::*;
+pub use liquidate_with_tokens::*;
+pub use liquidation_auction::*;
+pub use raydium::*;
+pub use raydium_cpmm::*;
+pub use raydium_clmm::*;
\ No newline at end of file
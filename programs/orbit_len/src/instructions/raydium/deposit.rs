@@ -1,17 +1,18 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    token::{ Token, ID as TOKEN_PROGRAM_ID },
-    token_interface::{ Mint, TokenAccount },
+    token::{ transfer_checked, Token, TransferChecked, ID as TOKEN_PROGRAM_ID },
+    token_interface::{ Mint, TokenAccount, TokenInterface },
 };
 use raydium_amm_cpi::Deposit;
-use crate::{ constants::*, bank::*, account::*, error::*, events::* };
+use crate::{ constants::*, bank::*, account::*, error::*, events::*, utils, raydium_amm::* };
+use crate::instructions::dex_adapter::*;
 
 #[derive(Accounts, Clone)]
 pub struct ProxyDeposit<'info> {
     /**
      * Raydium accounts
      */
-    /// CHECK: Safe
+    /// CHECK: Checked against RAYDIUM_AMM_PROGRAM_IDS
     pub amm_program: UncheckedAccount<'info>,
     /// CHECK: Safe. Amm Account
     #[account(mut)]
@@ -56,7 +57,10 @@ pub struct ProxyDeposit<'info> {
         bump,
     )]
     pub orbitlen_account: AccountLoader<'info, OrbitlenAccount>,
-    #[account(mint::decimals = COMMON_TOKEN_DECIMALS)]
+    #[account(
+        mint::decimals = coin_bank.load()?.mint_decimals,
+        mint::token_program = coin_token_program,
+    )]
     pub coin_mint: InterfaceAccount<'info, Mint>,
     #[account(
         mut,
@@ -84,116 +88,202 @@ pub struct ProxyDeposit<'info> {
         bump = coin_bank.load()?.liquidity_vault_authority_bump,
     )]
     pub coin_bank_liquidity_vault_authority: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [BANK_SEED.as_bytes(), amm_lp_mint.key().as_ref()],
+        bump,
+        constraint = lp_bank.load()?.config.raydium_lp_config.amm == amm.key() @ OrbitlenError::InvalidRaydiumPoolAccount,
+    )]
+    pub lp_bank: AccountLoader<'info, Bank>,
+    /// CHECK: Seed constraint check
+    #[account(
+        mut,
+        seeds = [
+            LIQUIDITY_VAULT_SEED.as_bytes(),
+            lp_bank.key().as_ref(),
+        ],
+        bump = lp_bank.load()?.liquidity_vault_bump,
+    )]
+    pub lp_bank_liquidity_vault: AccountInfo<'info>,
     #[account(mut)]
     pub user_owner: Signer<'info>,
+    /// SPL Token program the Raydium AMM moves pool tokens with
     #[account(address = TOKEN_PROGRAM_ID)]
     pub token_program: Program<'info, Token>,
+    /// Token program of `coin_mint`, used for bank vault transfers
+    pub coin_token_program: Interface<'info, TokenInterface>,
+    /**
+     * Optional pc bank, when set the pc leg is sourced from and settled into it as well
+     */
+    #[account(mut)]
+    pub pc_bank: Option<AccountLoader<'info, Bank>>,
+    /// CHECK: Checked against pc_bank
+    #[account(mut)]
+    pub pc_bank_liquidity_vault: Option<AccountInfo<'info>>,
+    /// CHECK: Checked against pc_bank
+    pub pc_bank_liquidity_vault_authority: Option<AccountInfo<'info>>,
+    pub pc_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// Token program of `pc_mint`, used for bank vault transfers
+    pub pc_token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+impl<'info> DexAdapter<'info> for ProxyDeposit<'info> {
+    fn get_orbitlen_account(&self) -> &AccountLoader<'info, OrbitlenAccount> {
+        &self.orbitlen_account
+    }
+
+    fn get_user_owner(&self) -> AccountInfo<'info> {
+        self.user_owner.to_account_info()
+    }
+
+    /// Checks the Raydium accounts against the pool state, see `RaydiumAmmInfo::load_pool`
+    fn validate(&self) -> Result<()> {
+        let amm_info = RaydiumAmmInfo::load_pool(&RaydiumPoolAccounts {
+            amm_program: &self.amm_program,
+            amm: &self.amm,
+            amm_authority: &self.amm_authority,
+            amm_open_orders: &self.amm_open_orders,
+            amm_coin_vault: &self.amm_coin_vault,
+            amm_pc_vault: &self.amm_pc_vault,
+            market: &self.market,
+            coin_mint: self.coin_mint.to_account_info(),
+            pc_mint: self.pc_mint.as_ref().map(|pc_mint| pc_mint.to_account_info()),
+        })?;
+        require_keys_eq!(
+            amm_info.target_orders,
+            self.amm_target_orders.key(),
+            OrbitlenError::InvalidRaydiumPoolAccount
+        );
+        require_keys_eq!(
+            amm_info.lp_mint,
+            self.amm_lp_mint.key(),
+            OrbitlenError::InvalidRaydiumPoolAccount
+        );
+
+        Ok(())
+    }
+
+    fn get_coin_leg(&self) -> BankLeg<'_, 'info> {
+        BankLeg {
+            bank: &self.coin_bank,
+            bank_liquidity_vault: self.coin_bank_liquidity_vault.to_account_info(),
+            bank_liquidity_vault_authority: self.coin_bank_liquidity_vault_authority.to_account_info(),
+            mint: &self.coin_mint,
+            token_program: self.coin_token_program.to_account_info(),
+            user_token_account: self.user_token_coin.to_account_info(),
+        }
+    }
+
+    fn get_pc_leg(&self) -> Result<Option<BankLeg<'_, 'info>>> {
+        BankLeg::try_from_optional(
+            self.pc_bank.as_ref(),
+            self.pc_bank_liquidity_vault.as_ref(),
+            self.pc_bank_liquidity_vault_authority.as_ref(),
+            self.pc_mint.as_deref(),
+            self.pc_token_program.as_ref(),
+            self.get_user_pc_token_account()
+        )
+    }
+
+    fn get_user_pc_token_account(&self) -> Option<AccountInfo<'info>> {
+        Some(self.user_token_pc.to_account_info())
+    }
+
+    fn get_lp_bank(&self) -> Option<Pubkey> {
+        Some(self.lp_bank.key())
+    }
 }
 
 pub fn deposit_process<'info>(
-    ctx: Context<'_, '_, '_, 'info, ProxyDeposit<'info>>,
+    ctx: Context<'_, '_, 'info, 'info, ProxyDeposit<'info>>,
     coin_amount: u64,
-    pc_amount: u64
+    pc_amount: u64,
+    min_lp_amount: u64
 ) -> Result<()> {
-    let ProxyDeposit {
-        orbitlen_account: orbitlen_account_loader,
-        coin_bank_liquidity_vault,
-        token_program,
-        coin_bank: coin_bank_loader,
-        coin_mint,
-        user_token_coin,
-        coin_bank_liquidity_vault_authority,
-        amm,
-        market,
-        user_owner,
-        ..
-    } = ctx.accounts;
+    let accounts = &ctx.accounts;
+
+    let lp_amount_before = utils::get_token_account_amount(&accounts.user_token_lp)?;
+
+    let (coin_amount_used, pc_amount_used) = accounts.borrow_for_cpi(
+        coin_amount,
+        pc_amount,
+        ctx.remaining_accounts,
+        || {
+            // user coin/pc token accounts --> coin/pc vaults in raydium amm pool
+            let cpi_accounts = Deposit {
+                amm: accounts.amm.clone(),
+                amm_authority: accounts.amm_authority.clone(),
+                amm_open_orders: accounts.amm_open_orders.clone(),
+                amm_target_orders: accounts.amm_target_orders.clone(),
+                amm_lp_mint: accounts.amm_lp_mint.clone(),
+                amm_coin_vault: accounts.amm_coin_vault.clone(),
+                amm_pc_vault: accounts.amm_pc_vault.clone(),
+                market: accounts.market.clone(),
+                market_event_queue: accounts.market_event_queue.clone(),
+                user_token_coin: accounts.user_token_coin.clone(),
+                user_token_pc: accounts.user_token_pc.clone(),
+                user_token_lp: accounts.user_token_lp.clone(),
+                user_owner: accounts.user_owner.clone(),
+                token_program: accounts.token_program.clone(),
+            };
+            let cpi_program = accounts.amm_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+            raydium_amm_cpi::deposit(cpi_ctx, coin_amount, pc_amount, 0)
+        }
+    )?;
+
+    let lp_amount = utils::get_token_account_amount(&accounts.user_token_lp)?
+        .checked_sub(lp_amount_before)
+        .ok_or(OrbitlenError::MathError)?;
+
+    debug!("lp_amount: {}, min_lp_amount: {}", lp_amount, min_lp_amount);
+
+    require_gte!(lp_amount, min_lp_amount, OrbitlenError::SlippageToleranceExceeded);
 
     {
+        // user lp token account --> lp bank vault, escrowed as collateral of the user
         let clock = Clock::get().map_err(|_| OrbitlenError::GetClockFailed)?;
 
-        let mut coin_bank = coin_bank_loader.load_mut()?;
-        msg!("coin_bank: {:?}", coin_bank);
-
-        let coin_liquidity_vault_authority_bump = coin_bank.liquidity_vault_authority_bump;
+        let mut lp_bank = accounts.lp_bank.load_mut()?;
+        let lp_mint_decimals = lp_bank.mint_decimals;
 
-        let mut orbitlen_account = orbitlen_account_loader.load_mut()?;
-        msg!("orbitlen_account: {:?}", orbitlen_account);
+        let mut orbitlen_account = accounts.orbitlen_account.load_mut()?;
 
-        coin_bank.accrue_interest(clock.unix_timestamp)?;
+        lp_bank.accrue_interest(clock.unix_timestamp, &accounts.lp_bank.key())?;
 
-        let mut coin_bank_account = BankAccountWrapper::find_or_create(
-            &coin_bank_loader.key(),
-            &mut coin_bank,
+        let mut lp_bank_account = BankAccountWrapper::find_or_create(
+            &accounts.lp_bank.key(),
+            &mut lp_bank,
             &mut orbitlen_account.lending_account
         )?;
 
-        coin_bank_account.decrease_balance(coin_amount)?;
-
-        //coin vault --> user coin token account
-        let signer_seeds: &[&[&[u8]]] = &[
-            &[
-                BankVaultType::Liquidity.get_authority_seed(),
-                &coin_bank_loader.key().to_bytes(),
-                &[coin_liquidity_vault_authority_bump],
-            ],
-        ];
-
-        coin_bank_account.withdraw_spl_transfer(
-            coin_amount,
-            coin_bank_liquidity_vault.to_account_info(),
-            user_token_coin.to_account_info(),
-            coin_bank_liquidity_vault_authority.to_account_info(),
-            coin_mint,
-            token_program.to_account_info(),
-            signer_seeds,
-            ctx.remaining_accounts
-        )?;
-    }
-
-    let orbitlen_account_key = orbitlen_account_loader.key();
-    let orbitlen_account_authority = orbitlen_account_loader.load()?.authority;
-    let coin_mint_key = coin_mint.key();
-    let amm_key = *amm.key;
-    let market_key = *market.key;
-    let signer_key = *user_owner.key;
+        lp_bank_account.deposit(lp_amount)?;
 
-    {
-        // user coin token account --> coin vault in raydium amm pool
-        let accounts = ctx.accounts;
-        let cpi_accounts = Deposit {
-            amm: accounts.amm.clone(),
-            amm_authority: accounts.amm_authority.clone(),
-            amm_open_orders: accounts.amm_open_orders.clone(),
-            amm_target_orders: accounts.amm_target_orders.clone(),
-            amm_lp_mint: accounts.amm_lp_mint.clone(),
-            amm_coin_vault: accounts.amm_coin_vault.clone(),
-            amm_pc_vault: accounts.amm_pc_vault.clone(),
-            market: accounts.market.clone(),
-            market_event_queue: accounts.market_event_queue.clone(),
-            user_token_coin: accounts.user_token_coin.clone(),
-            user_token_pc: accounts.user_token_pc.clone(),
-            user_token_lp: accounts.user_token_lp.clone(),
-            user_owner: accounts.user_owner.clone(),
-            token_program: accounts.token_program.clone(),
-        };
-        let cpi_program = accounts.amm_program.to_account_info();
-        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-        raydium_amm_cpi::deposit(cpi_ctx, coin_amount, pc_amount, 0)?;
+        transfer_checked(
+            CpiContext::new(accounts.token_program.to_account_info(), TransferChecked {
+                from: accounts.user_token_lp.to_account_info(),
+                mint: accounts.amm_lp_mint.to_account_info(),
+                to: accounts.lp_bank_liquidity_vault.to_account_info(),
+                authority: accounts.user_owner.to_account_info(),
+            }),
+            lp_amount,
+            lp_mint_decimals
+        )?;
     }
 
     emit!(RaydiumDepositEvent {
         header: RaydiumEventHeader {
-            amm: amm_key,
-            market: market_key,
-            signer: signer_key,
-            orbitlen_account: orbitlen_account_key,
-            orbitlen_account_authority: orbitlen_account_authority,
+            amm: accounts.amm.key(),
+            market: accounts.market.key(),
+            signer: accounts.user_owner.key(),
+            orbitlen_account: accounts.orbitlen_account.key(),
+            orbitlen_account_authority: accounts.orbitlen_account.load()?.authority,
         },
-        coin_mint: coin_mint_key,
-        coin_amount,
-        pc_amount,
+        coin_mint: accounts.coin_mint.key(),
+        coin_amount: coin_amount_used,
+        pc_amount: pc_amount_used,
+        lp_amount,
     });
 
-    Ok(())
+    accounts.check_health(ctx.remaining_accounts)
 }
@@ -1,10 +1,8 @@
 pub mod deposit;
 pub mod withdraw;
-pub mod swap_base_in;
-pub mod swap_base_out;
+pub mod swap;
 
 
 pub use withdraw::*;
 pub use deposit::*;
-pub use swap_base_in::*;
-pub use swap_base_out::*;
+pub use swap::*;
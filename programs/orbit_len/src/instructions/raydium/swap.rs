@@ -0,0 +1,282 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::{ Token, ID as TOKEN_PROGRAM_ID },
+    token_interface::{ Mint, TokenAccount, TokenInterface },
+};
+use raydium_amm_cpi::{ SwapBaseIn, SwapBaseOut };
+use crate::{ constants::*, bank::*, account::*, error::*, events::*, math, raydium_amm::* };
+use crate::instructions::dex_adapter::*;
+
+/// Shared by `raydium_swap_base_in` and `raydium_swap_base_out`, like the AMM program's own
+/// `SwapBaseIn` and `SwapBaseOut` accounts.
+#[derive(Accounts, Clone)]
+pub struct ProxySwap<'info> {
+    /**
+     * Raydium accounts
+     */
+    /// CHECK: Checked against RAYDIUM_AMM_PROGRAM_IDS
+    pub amm_program: UncheckedAccount<'info>,
+    /// CHECK: Safe. amm Account
+    #[account(mut)]
+    pub amm: UncheckedAccount<'info>,
+    /// CHECK: Safe. Amm authority Account
+    #[account()]
+    pub amm_authority: UncheckedAccount<'info>,
+    /// CHECK: Safe. amm open_orders Account
+    #[account(mut)]
+    pub amm_open_orders: UncheckedAccount<'info>,
+    /// CHECK: Safe. amm_coin_vault Amm Account to swap FROM or To,
+    #[account(mut)]
+    pub amm_coin_vault: UncheckedAccount<'info>,
+    /// CHECK: Safe. amm_pc_vault Amm Account to swap FROM or To,
+    #[account(mut)]
+    pub amm_pc_vault: UncheckedAccount<'info>,
+    /// CHECK: Safe.OpenBook program id
+    pub market_program: UncheckedAccount<'info>,
+    /// CHECK: Safe. OpenBook market Account. OpenBook program is the owner.
+    #[account(mut)]
+    pub market: UncheckedAccount<'info>,
+    /// CHECK: Safe. bids Account
+    #[account(mut)]
+    pub market_bids: UncheckedAccount<'info>,
+    /// CHECK: Safe. asks Account
+    #[account(mut)]
+    pub market_asks: UncheckedAccount<'info>,
+    /// CHECK: Safe. event_q Account
+    #[account(mut)]
+    pub market_event_queue: UncheckedAccount<'info>,
+    /// CHECK: Safe. coin_vault Account
+    #[account(mut)]
+    pub market_coin_vault: UncheckedAccount<'info>,
+    /// CHECK: Safe. pc_vault Account
+    #[account(mut)]
+    pub market_pc_vault: UncheckedAccount<'info>,
+    /// CHECK: Safe. vault_signer Account
+    #[account(mut)]
+    pub market_vault_signer: UncheckedAccount<'info>,
+    /// CHECK: Safe. user source token Account. user Account to swap from.
+    #[account(mut)]
+    pub user_token_source: UncheckedAccount<'info>,
+    /// CHECK: Safe. user destination token Account. user Account to swap to.
+    #[account(mut)]
+    pub user_token_destination: UncheckedAccount<'info>,
+    /// CHECK: Safe. user owner Account
+    #[account(mut)]
+    pub user_source_owner: Signer<'info>,
+    /**
+     * Orbitlen accounts
+     */
+    #[account(
+        mut,
+        seeds = [ORBITLEN_ACCOUNT_SEED.as_bytes(), user_source_owner.key().as_ref()],
+        bump,
+    )]
+    pub orbitlen_account: AccountLoader<'info, OrbitlenAccount>,
+    #[account(
+        mut,        
+        seeds = [BANK_SEED.as_bytes(), coin_mint.key().as_ref()],
+        bump
+    )]
+    pub bank: AccountLoader<'info, Bank>,
+    /// CHECK: Seed constraint check
+    #[account(
+        mut,
+        seeds = [
+            LIQUIDITY_VAULT_SEED.as_bytes(),
+            bank.key().as_ref(),
+        ],
+        bump = bank.load()?.liquidity_vault_bump,
+    )]
+    pub bank_liquidity_vault: AccountInfo<'info>,
+    /// CHECK: Seed constraint check
+    #[account(
+            mut,
+            seeds = [
+                LIQUIDITY_VAULT_AUTHORITY_SEED.as_bytes(),
+                bank.key().as_ref(),
+            ],
+            bump = bank.load()?.liquidity_vault_authority_bump,
+        )]
+    pub bank_liquidity_vault_authority: AccountInfo<'info>,
+    #[account(mut)]
+    pub user_coin_token_account: InterfaceAccount<'info, TokenAccount>,
+    #[account(
+        mint::decimals = bank.load()?.mint_decimals,
+        mint::token_program = coin_token_program,
+    )]
+    pub coin_mint: InterfaceAccount<'info, Mint>,
+    /// SPL Token program the Raydium AMM moves pool tokens with
+    #[account(address = TOKEN_PROGRAM_ID)]
+    pub token_program: Program<'info, Token>,
+    /// Token program of `coin_mint`, used for bank vault transfers
+    pub coin_token_program: Interface<'info, TokenInterface>,
+    /**
+     * Optional pc bank, when set the pc leg is sourced from and settled into it as well
+     */
+    #[account(mut)]
+    pub pc_bank: Option<AccountLoader<'info, Bank>>,
+    /// CHECK: Checked against pc_bank
+    #[account(mut)]
+    pub pc_bank_liquidity_vault: Option<AccountInfo<'info>>,
+    /// CHECK: Checked against pc_bank
+    pub pc_bank_liquidity_vault_authority: Option<AccountInfo<'info>>,
+    #[account(mut)]
+    pub user_pc_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+    pub pc_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// Token program of `pc_mint`, used for bank vault transfers
+    pub pc_token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+impl<'info> DexAdapter<'info> for ProxySwap<'info> {
+    fn get_orbitlen_account(&self) -> &AccountLoader<'info, OrbitlenAccount> {
+        &self.orbitlen_account
+    }
+
+    fn get_user_owner(&self) -> AccountInfo<'info> {
+        self.user_source_owner.to_account_info()
+    }
+
+    /// Checks the Raydium accounts against the pool state, see `RaydiumAmmInfo::load_pool`
+    fn validate(&self) -> Result<()> {
+        let amm_info = RaydiumAmmInfo::load_pool(&RaydiumPoolAccounts {
+            amm_program: &self.amm_program,
+            amm: &self.amm,
+            amm_authority: &self.amm_authority,
+            amm_open_orders: &self.amm_open_orders,
+            amm_coin_vault: &self.amm_coin_vault,
+            amm_pc_vault: &self.amm_pc_vault,
+            market: &self.market,
+            coin_mint: self.coin_mint.to_account_info(),
+            pc_mint: self.pc_mint.as_ref().map(|pc_mint| pc_mint.to_account_info()),
+        })?;
+        require_keys_eq!(
+            amm_info.market_program,
+            self.market_program.key(),
+            OrbitlenError::InvalidRaydiumPoolAccount
+        );
+
+        Ok(())
+    }
+
+    fn get_coin_leg(&self) -> BankLeg<'_, 'info> {
+        BankLeg {
+            bank: &self.bank,
+            bank_liquidity_vault: self.bank_liquidity_vault.to_account_info(),
+            bank_liquidity_vault_authority: self.bank_liquidity_vault_authority.to_account_info(),
+            mint: &self.coin_mint,
+            token_program: self.coin_token_program.to_account_info(),
+            user_token_account: self.user_coin_token_account.to_account_info(),
+        }
+    }
+
+    fn get_pc_leg(&self) -> Result<Option<BankLeg<'_, 'info>>> {
+        BankLeg::try_from_optional(
+            self.pc_bank.as_ref(),
+            self.pc_bank_liquidity_vault.as_ref(),
+            self.pc_bank_liquidity_vault_authority.as_ref(),
+            self.pc_mint.as_deref(),
+            self.pc_token_program.as_ref(),
+            self.user_pc_token_account.as_ref().map(|account| account.to_account_info())
+        )
+    }
+}
+
+/// `SwapBaseIn` and `SwapBaseOut` take the same accounts
+macro_rules! swap_cpi_accounts {
+    ($cpi_accounts:ident, $accounts:expr) => {
+        $cpi_accounts {
+            amm: $accounts.amm.clone(),
+            amm_authority: $accounts.amm_authority.clone(),
+            amm_open_orders: $accounts.amm_open_orders.clone(),
+            amm_coin_vault: $accounts.amm_coin_vault.clone(),
+            amm_pc_vault: $accounts.amm_pc_vault.clone(),
+            market_program: $accounts.market_program.clone(),
+            market: $accounts.market.clone(),
+            market_bids: $accounts.market_bids.clone(),
+            market_asks: $accounts.market_asks.clone(),
+            market_event_queue: $accounts.market_event_queue.clone(),
+            market_coin_vault: $accounts.market_coin_vault.clone(),
+            market_pc_vault: $accounts.market_pc_vault.clone(),
+            market_vault_signer: $accounts.market_vault_signer.clone(),
+            user_token_source: $accounts.user_token_source.clone(),
+            user_token_destination: $accounts.user_token_destination.clone(),
+            user_source_owner: $accounts.user_source_owner.clone(),
+            token_program: $accounts.token_program.clone(),
+        }
+    };
+}
+
+pub fn swap_base_in_process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ProxySwap<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64
+) -> Result<()> {
+    swap_process(ctx, |accounts| {
+        let cpi_ctx = CpiContext::new(
+            accounts.amm_program.to_account_info(),
+            swap_cpi_accounts!(SwapBaseIn, accounts)
+        );
+        raydium_amm_cpi::swap_base_in(cpi_ctx, amount_in, minimum_amount_out)
+    })
+}
+
+pub fn swap_base_out_process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ProxySwap<'info>>,
+    max_amount_in: u64,
+    amount_out: u64
+) -> Result<()> {
+    swap_process(ctx, |accounts| {
+        let cpi_ctx = CpiContext::new(
+            accounts.amm_program.to_account_info(),
+            swap_cpi_accounts!(SwapBaseOut, accounts)
+        );
+        raydium_amm_cpi::swap_base_out(cpi_ctx, max_amount_in, amount_out)
+    })
+}
+
+/// Runs the swap `cpi`, settles both legs with their banks and emits `RaydiumSwapEvent`
+fn swap_process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ProxySwap<'info>>,
+    cpi: impl FnOnce(&ProxySwap<'info>) -> Result<()>
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    let (swap_amount_in, swap_amount_out) = accounts.settle_cpi(ctx.remaining_accounts, || {
+        measure_swap(&accounts.user_token_source, &accounts.user_token_destination, || {
+            cpi(accounts)
+        })
+    })?;
+
+    let direction = if accounts.user_token_source.key() == accounts.user_coin_token_account.key() {
+        RaydiumSwapDirection::CoinToPc
+    } else {
+        RaydiumSwapDirection::PcToCoin
+    };
+    let (coin_amount, pc_amount) = match direction {
+        RaydiumSwapDirection::CoinToPc => (swap_amount_in, swap_amount_out),
+        RaydiumSwapDirection::PcToCoin => (swap_amount_out, swap_amount_in),
+    };
+    let amm_info = RaydiumAmmInfo::load(&accounts.amm)?;
+
+    emit!(RaydiumSwapEvent {
+        header: RaydiumEventHeader {
+            amm: accounts.amm.key(),
+            market: accounts.market.key(),
+            signer: accounts.user_source_owner.key(),
+            orbitlen_account: accounts.orbitlen_account.key(),
+            orbitlen_account_authority: accounts.orbitlen_account.load()?.authority,
+        },
+        coin_mint: accounts.coin_mint.key(),
+        direction,
+        amount_in: swap_amount_in,
+        amount_out: swap_amount_out,
+        executed_price: if coin_amount > 0 {
+            math::as_ui_amount(pc_amount, amm_info.pc_decimals as u8) /
+                math::as_ui_amount(coin_amount, amm_info.coin_decimals as u8)
+        } else {
+            0.0
+        },
+    });
+
+    accounts.check_health(ctx.remaining_accounts)
+}
@@ -1,17 +1,18 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    token::{ Token, ID as TOKEN_PROGRAM_ID },
-    token_interface::{ Mint, TokenAccount },
+    token::{ transfer_checked, Token, TransferChecked, ID as TOKEN_PROGRAM_ID },
+    token_interface::{ Mint, TokenAccount, TokenInterface },
 };
 use raydium_amm_cpi::Withdraw;
-use crate::{ constants::*, bank::*, account::*, error::*, events::* };
+use crate::{ constants::*, bank::*, account::*, error::*, events::*, utils, raydium_amm::* };
+use crate::instructions::dex_adapter::*;
 
 #[derive(Accounts, Clone)]
 pub struct ProxyWithdraw<'info> {
     /**
      * Raydium accounts
      */
-    /// CHECK: Safe
+    /// CHECK: Checked against RAYDIUM_AMM_PROGRAM_IDS
     pub amm_program: UncheckedAccount<'info>,
     /// CHECK: Safe. Amm account
     #[account(mut)]
@@ -93,106 +94,248 @@ pub struct ProxyWithdraw<'info> {
         bump = bank.load()?.liquidity_vault_bump,
     )]
     pub bank_liquidity_vault: AccountInfo<'info>,
+    /// CHECK: Seed constraint check
+    #[account(
+        seeds = [
+            LIQUIDITY_VAULT_AUTHORITY_SEED.as_bytes(),
+            bank.key().as_ref(),
+        ],
+        bump = bank.load()?.liquidity_vault_authority_bump,
+    )]
+    pub bank_liquidity_vault_authority: AccountInfo<'info>,
+    #[account(
+        mut,
+        seeds = [BANK_SEED.as_bytes(), amm_lp_mint.key().as_ref()],
+        bump,
+        constraint = lp_bank.load()?.config.raydium_lp_config.amm == amm.key() @ OrbitlenError::InvalidRaydiumPoolAccount,
+    )]
+    pub lp_bank: AccountLoader<'info, Bank>,
+    /// CHECK: Seed constraint check
+    #[account(
+        mut,
+        seeds = [
+            LIQUIDITY_VAULT_SEED.as_bytes(),
+            lp_bank.key().as_ref(),
+        ],
+        bump = lp_bank.load()?.liquidity_vault_bump,
+    )]
+    pub lp_bank_liquidity_vault: AccountInfo<'info>,
+    /// CHECK: Seed constraint check
+    #[account(
+        seeds = [
+            LIQUIDITY_VAULT_AUTHORITY_SEED.as_bytes(),
+            lp_bank.key().as_ref(),
+        ],
+        bump = lp_bank.load()?.liquidity_vault_authority_bump,
+    )]
+    pub lp_bank_liquidity_vault_authority: AccountInfo<'info>,
     #[account(mut)]
     pub user_coin_token_account: InterfaceAccount<'info, TokenAccount>,
-    #[account(mint::decimals = COMMON_TOKEN_DECIMALS)]
+    #[account(
+        mint::decimals = bank.load()?.mint_decimals,
+        mint::token_program = coin_token_program,
+    )]
     pub coin_mint: InterfaceAccount<'info, Mint>,
+    /// SPL Token program the Raydium AMM moves pool tokens with
     #[account(address = TOKEN_PROGRAM_ID)]
     pub token_program: Program<'info, Token>,
+    /// Token program of `coin_mint`, used for bank vault transfers
+    pub coin_token_program: Interface<'info, TokenInterface>,
+    /**
+     * Optional pc bank, when set the pc leg is sourced from and settled into it as well
+     */
+    #[account(mut)]
+    pub pc_bank: Option<AccountLoader<'info, Bank>>,
+    /// CHECK: Checked against pc_bank
+    #[account(mut)]
+    pub pc_bank_liquidity_vault: Option<AccountInfo<'info>>,
+    /// CHECK: Checked against pc_bank
+    pub pc_bank_liquidity_vault_authority: Option<AccountInfo<'info>>,
+    pub pc_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// Token program of `pc_mint`, used for bank vault transfers
+    pub pc_token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+impl<'info> DexAdapter<'info> for ProxyWithdraw<'info> {
+    fn get_orbitlen_account(&self) -> &AccountLoader<'info, OrbitlenAccount> {
+        &self.orbitlen_account
+    }
+
+    fn get_user_owner(&self) -> AccountInfo<'info> {
+        self.user_owner.to_account_info()
+    }
+
+    /// Checks the Raydium accounts against the pool state, see `RaydiumAmmInfo::load_pool`
+    fn validate(&self) -> Result<()> {
+        let amm_info = RaydiumAmmInfo::load_pool(&RaydiumPoolAccounts {
+            amm_program: &self.amm_program,
+            amm: &self.amm,
+            amm_authority: &self.amm_authority,
+            amm_open_orders: &self.amm_open_orders,
+            amm_coin_vault: &self.amm_coin_vault,
+            amm_pc_vault: &self.amm_pc_vault,
+            market: &self.market,
+            coin_mint: self.coin_mint.to_account_info(),
+            pc_mint: self.pc_mint.as_ref().map(|pc_mint| pc_mint.to_account_info()),
+        })?;
+        require_keys_eq!(
+            amm_info.target_orders,
+            self.amm_target_orders.key(),
+            OrbitlenError::InvalidRaydiumPoolAccount
+        );
+        require_keys_eq!(
+            amm_info.lp_mint,
+            self.amm_lp_mint.key(),
+            OrbitlenError::InvalidRaydiumPoolAccount
+        );
+        require_keys_eq!(
+            amm_info.market_program,
+            self.market_program.key(),
+            OrbitlenError::InvalidRaydiumPoolAccount
+        );
+
+        Ok(())
+    }
+
+    fn get_coin_leg(&self) -> BankLeg<'_, 'info> {
+        BankLeg {
+            bank: &self.bank,
+            bank_liquidity_vault: self.bank_liquidity_vault.to_account_info(),
+            bank_liquidity_vault_authority: self.bank_liquidity_vault_authority.to_account_info(),
+            mint: &self.coin_mint,
+            token_program: self.coin_token_program.to_account_info(),
+            user_token_account: self.user_coin_token_account.to_account_info(),
+        }
+    }
+
+    fn get_pc_leg(&self) -> Result<Option<BankLeg<'_, 'info>>> {
+        BankLeg::try_from_optional(
+            self.pc_bank.as_ref(),
+            self.pc_bank_liquidity_vault.as_ref(),
+            self.pc_bank_liquidity_vault_authority.as_ref(),
+            self.pc_mint.as_deref(),
+            self.pc_token_program.as_ref(),
+            Some(self.user_token_pc.to_account_info())
+        )
+    }
+
+    fn get_lp_bank(&self) -> Option<Pubkey> {
+        Some(self.lp_bank.key())
+    }
 }
 
 pub fn withdraw_process<'info>(
-    ctx: Context<'_, '_, '_, 'info, ProxyWithdraw<'info>>,
-    amount: u64
+    ctx: Context<'_, '_, 'info, 'info, ProxyWithdraw<'info>>,
+    amount: u64,
+    min_coin_amount: u64,
+    min_pc_amount: u64
 ) -> Result<()> {
-    let accounts = ctx.accounts;
-
-    let coin_amount_before = accounts.user_coin_token_account.amount;
-    msg!("coin_amount_before: {}", coin_amount_before);
-
-    let cpi_accounts = Withdraw {
-        amm: accounts.amm.clone(),
-        amm_authority: accounts.amm_authority.clone(),
-        amm_open_orders: accounts.amm_open_orders.clone(),
-        amm_target_orders: accounts.amm_target_orders.clone(),
-        amm_lp_mint: accounts.amm_lp_mint.clone(),
-        amm_coin_vault: accounts.amm_coin_vault.clone(),
-        amm_pc_vault: accounts.amm_pc_vault.clone(),
-        market_program: accounts.market_program.clone(),
-        market: accounts.market.clone(),
-        market_coin_vault: accounts.market_coin_vault.clone(),
-        market_pc_vault: accounts.market_pc_vault.clone(),
-        market_vault_signer: accounts.market_vault_signer.clone(),
-        user_token_lp: accounts.user_token_lp.clone(),
-        user_token_coin: accounts.user_token_coin.clone(),
-        user_token_pc: accounts.user_token_pc.clone(),
-        user_owner: accounts.user_owner.clone(),
-        market_event_q: accounts.market_event_q.clone(),
-        market_bids: accounts.market_bids.clone(),
-        market_asks: accounts.market_asks.clone(),
-        token_program: accounts.token_program.clone(),
-    };
-    let cpi_program = accounts.amm_program.to_account_info();
-    let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
-    raydium_amm_cpi::withdraw(cpi_ctx, amount)?;
-
-    accounts.user_coin_token_account.reload()?;
-    let coin_amount_after = accounts.user_coin_token_account.amount;
-
-    msg!("coin_amount_after: {}", coin_amount_after);
-
-    let deposit_amount = coin_amount_after - coin_amount_before;
-
-    msg!("transfer amount:{}", deposit_amount);
-
-    let ProxyWithdraw {
-        orbitlen_account: orbitlen_account_loader,
-        user_owner,
-        user_coin_token_account,
-        bank_liquidity_vault,
-        token_program,
-        bank: bank_loader,
-        coin_mint,
-        ..
-    } = accounts;
-
-    let clock = Clock::get().map_err(|_| OrbitlenError::GetClockFailed)?;
-
-    let mut bank = bank_loader.load_mut()?;
-    msg!("bank: {:?}", bank);
-    let mut orbitlen_account = orbitlen_account_loader.load_mut()?;
-    msg!("orbitlen_account: {:?}", orbitlen_account);
-
-    bank.accrue_interest(clock.unix_timestamp)?;
-
-    let mut bank_account = BankAccountWrapper::find_or_create(
-        &bank_loader.key(),
-        &mut bank,
-        &mut orbitlen_account.lending_account
-    )?;
-
-    bank_account.deposit(deposit_amount.into())?;
-
-    bank_account.deposit_spl_transfer(
-        deposit_amount,
-        user_coin_token_account.to_account_info(),
-        bank_liquidity_vault.to_account_info(),
-        user_owner.to_account_info(),
-        coin_mint,
-        token_program.to_account_info(),
-        ctx.remaining_accounts
-    )?;
-
-    emit!(LendingAccountDepositEvent {
-        header: AccountEventHeader {
-            signer: user_owner.key(),
-            orbitlen_account: orbitlen_account_loader.key(),
-            orbitlen_account_authority: orbitlen_account.authority,
+    let accounts = &ctx.accounts;
+
+    accounts.validate()?;
+
+    {
+        // lp bank vault --> user lp token account, the escrowed LP can only leave to be burned
+        let clock = Clock::get().map_err(|_| OrbitlenError::GetClockFailed)?;
+
+        let mut lp_bank = accounts.lp_bank.load_mut()?;
+        let lp_mint_decimals = lp_bank.mint_decimals;
+        let lp_liquidity_vault_authority_bump = lp_bank.liquidity_vault_authority_bump;
+
+        let mut orbitlen_account = accounts.orbitlen_account.load_mut()?;
+
+        lp_bank.accrue_interest(clock.unix_timestamp, &accounts.lp_bank.key())?;
+
+        let mut lp_bank_account = BankAccountWrapper::find(
+            &accounts.lp_bank.key(),
+            &mut lp_bank,
+            &mut orbitlen_account.lending_account
+        )?;
+
+        // LP banks are not borrowable, so this fails rather than open a liability
+        lp_bank_account.withdraw(amount)?;
+
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[
+                BankVaultType::Liquidity.get_authority_seed(),
+                &accounts.lp_bank.key().to_bytes(),
+                &[lp_liquidity_vault_authority_bump],
+            ],
+        ];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: accounts.lp_bank_liquidity_vault.to_account_info(),
+                    mint: accounts.amm_lp_mint.to_account_info(),
+                    to: accounts.user_token_lp.to_account_info(),
+                    authority: accounts.lp_bank_liquidity_vault_authority.to_account_info(),
+                },
+                signer_seeds
+            ),
+            amount,
+            lp_mint_decimals
+        )?;
+    }
+
+    // settlement deposits the payout into the banks, so it is measured around the CPI
+    let (coin_amount, pc_amount) = accounts.settle_cpi(ctx.remaining_accounts, || {
+        let coin_amount_before = accounts.get_coin_leg().get_user_token_amount()?;
+        let pc_amount_before = utils::get_token_account_amount(&accounts.user_token_pc)?;
+
+        let cpi_accounts = Withdraw {
+            amm: accounts.amm.clone(),
+            amm_authority: accounts.amm_authority.clone(),
+            amm_open_orders: accounts.amm_open_orders.clone(),
+            amm_target_orders: accounts.amm_target_orders.clone(),
+            amm_lp_mint: accounts.amm_lp_mint.clone(),
+            amm_coin_vault: accounts.amm_coin_vault.clone(),
+            amm_pc_vault: accounts.amm_pc_vault.clone(),
+            market_program: accounts.market_program.clone(),
+            market: accounts.market.clone(),
+            market_coin_vault: accounts.market_coin_vault.clone(),
+            market_pc_vault: accounts.market_pc_vault.clone(),
+            market_vault_signer: accounts.market_vault_signer.clone(),
+            user_token_lp: accounts.user_token_lp.clone(),
+            user_token_coin: accounts.user_token_coin.clone(),
+            user_token_pc: accounts.user_token_pc.clone(),
+            user_owner: accounts.user_owner.clone(),
+            market_event_q: accounts.market_event_q.clone(),
+            market_bids: accounts.market_bids.clone(),
+            market_asks: accounts.market_asks.clone(),
+            token_program: accounts.token_program.clone(),
+        };
+        let cpi_program = accounts.amm_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        raydium_amm_cpi::withdraw(cpi_ctx, amount)?;
+
+        let coin_amount_after = accounts.get_coin_leg().get_user_token_amount()?;
+        let pc_amount_after = utils::get_token_account_amount(&accounts.user_token_pc)?;
+        debug!("coin_amount_after: {}, pc_amount_after: {}", coin_amount_after, pc_amount_after);
+
+        Ok((
+            coin_amount_after.saturating_sub(coin_amount_before),
+            pc_amount_after.saturating_sub(pc_amount_before),
+        ))
+    })?;
+
+    require_gte!(coin_amount, min_coin_amount, OrbitlenError::SlippageToleranceExceeded);
+    require_gte!(pc_amount, min_pc_amount, OrbitlenError::SlippageToleranceExceeded);
+
+    emit!(RaydiumWithdrawEvent {
+        header: RaydiumEventHeader {
+            amm: accounts.amm.key(),
+            market: accounts.market.key(),
+            signer: accounts.user_owner.key(),
+            orbitlen_account: accounts.orbitlen_account.key(),
+            orbitlen_account_authority: accounts.orbitlen_account.load()?.authority,
         },
-        bank: bank_loader.key(),
-        mint: bank.mint,
-        amount: deposit_amount,
+        coin_mint: accounts.coin_mint.key(),
+        lp_amount: amount,
+        coin_amount,
+        pc_amount,
     });
 
-    Ok(())
+    accounts.check_health(ctx.remaining_accounts)
 }
@@ -0,0 +1,85 @@
+use anchor_lang::{ prelude::*, system_program::{ transfer, Transfer } };
+use anchor_spl::token::Token;
+use raydium_clmm_cpi::{ cpi, program::AmmV3 };
+use crate::{ constants::*, account::* };
+
+#[derive(Accounts)]
+pub struct ProxyClmmClosePosition<'info> {
+    /**
+     * Raydium CLMM accounts
+     */
+    pub clmm_program: Program<'info, AmmV3>,
+    /// CHECK: Safe. Position nft mint, burned by the CLMM program
+    #[account(mut)]
+    pub position_nft_mint: UncheckedAccount<'info>,
+    /// CHECK: Safe. Position nft account of `position_authority`, closed by the CLMM program
+    #[account(mut)]
+    pub position_nft_account: UncheckedAccount<'info>,
+    /// CHECK: Safe. Personal position account, closed by the CLMM program
+    #[account(mut)]
+    pub personal_position: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    /**
+     * Orbitlen accounts
+     */
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        seeds = [ORBITLEN_ACCOUNT_SEED.as_bytes(), owner.key().as_ref()],
+        bump,
+    )]
+    pub orbitlen_account: AccountLoader<'info, OrbitlenAccount>,
+    /// CHECK: Seed constraint check. Holds the position nft on behalf of `orbitlen_account`
+    #[account(
+        mut,
+        seeds = [CLMM_POSITION_AUTHORITY_SEED.as_bytes(), orbitlen_account.key().as_ref()],
+        bump,
+    )]
+    pub position_authority: AccountInfo<'info>,
+}
+
+/// Burns the position nft of an emptied position, liquidity and fees have to be taken out with
+/// `raydium_clmm_decrease_liquidity` first.
+pub fn clmm_close_position_process(ctx: Context<ProxyClmmClosePosition>) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    let orbitlen_account_key = accounts.orbitlen_account.key();
+    let signer_seeds: &[&[&[u8]]] = &[
+        &[
+            CLMM_POSITION_AUTHORITY_SEED.as_bytes(),
+            orbitlen_account_key.as_ref(),
+            &[ctx.bumps.position_authority],
+        ],
+    ];
+
+    let cpi_accounts = cpi::accounts::ClosePosition {
+        nft_owner: accounts.position_authority.to_account_info(),
+        position_nft_mint: accounts.position_nft_mint.to_account_info(),
+        position_nft_account: accounts.position_nft_account.to_account_info(),
+        personal_position: accounts.personal_position.to_account_info(),
+        system_program: accounts.system_program.to_account_info(),
+        token_program: accounts.token_program.to_account_info(),
+    };
+    let cpi_program = accounts.clmm_program.to_account_info();
+    let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+    cpi::close_position(cpi_ctx)?;
+
+    // the rent of the closed accounts is refunded to the nft owner, pass it on to the user
+    let lamports = accounts.position_authority.lamports();
+    if lamports > 0 {
+        transfer(
+            CpiContext::new_with_signer(
+                accounts.system_program.to_account_info(),
+                Transfer {
+                    from: accounts.position_authority.to_account_info(),
+                    to: accounts.owner.to_account_info(),
+                },
+                signer_seeds
+            ),
+            lamports
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,249 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::Token,
+    token_2022::Token2022,
+    token_interface::{ Mint, TokenInterface },
+};
+use raydium_clmm_cpi::{ cpi, program::AmmV3, states::PoolState };
+use crate::{ constants::*, bank::*, account::*, error::* };
+use crate::instructions::dex_adapter::*;
+
+#[derive(Accounts)]
+pub struct ProxyClmmDecreaseLiquidity<'info> {
+    /**
+     * Raydium CLMM accounts
+     */
+    pub clmm_program: Program<'info, AmmV3>,
+    /// CHECK: Safe. Position nft account of `position_authority`, checked by the CLMM program
+    pub position_nft_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+    /// CHECK: Safe. Protocol position account, checked by the CLMM program
+    #[account(mut)]
+    pub protocol_position: UncheckedAccount<'info>,
+    /// CHECK: Safe. Tick array of the lower tick, checked by the CLMM program
+    #[account(mut)]
+    pub tick_array_lower: UncheckedAccount<'info>,
+    /// CHECK: Safe. Tick array of the upper tick, checked by the CLMM program
+    #[account(mut)]
+    pub tick_array_upper: UncheckedAccount<'info>,
+    /// CHECK: Safe. Personal position account, checked by the CLMM program
+    #[account(mut)]
+    pub personal_position: UncheckedAccount<'info>,
+    /// CHECK: Safe. User token_0 account to credit, checked by the CLMM program
+    #[account(mut)]
+    pub token_account_0: UncheckedAccount<'info>,
+    /// CHECK: Safe. User token_1 account to credit, checked by the CLMM program
+    #[account(mut)]
+    pub token_account_1: UncheckedAccount<'info>,
+    /// CHECK: Address constraint check
+    #[account(mut, address = pool_state.load()?.token_vault_0)]
+    pub token_vault_0: UncheckedAccount<'info>,
+    /// CHECK: Address constraint check
+    #[account(mut, address = pool_state.load()?.token_vault_1)]
+    pub token_vault_1: UncheckedAccount<'info>,
+    #[account(address = pool_state.load()?.token_mint_0)]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = pool_state.load()?.token_mint_1)]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Program<'info, Token>,
+    pub token_program_2022: Program<'info, Token2022>,
+    /// CHECK: Address constraint check
+    #[account(address = SPL_MEMO_PROGRAM_ID)]
+    pub memo_program: UncheckedAccount<'info>,
+    /**
+     * Orbitlen accounts
+     */
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [ORBITLEN_ACCOUNT_SEED.as_bytes(), owner.key().as_ref()],
+        bump,
+    )]
+    pub orbitlen_account: AccountLoader<'info, OrbitlenAccount>,
+    /// CHECK: Seed constraint check. Holds the position nft on behalf of `orbitlen_account`
+    #[account(
+        seeds = [CLMM_POSITION_AUTHORITY_SEED.as_bytes(), orbitlen_account.key().as_ref()],
+        bump,
+    )]
+    pub position_authority: AccountInfo<'info>,
+    #[account(
+        mint::decimals = coin_bank.load()?.mint_decimals,
+        mint::token_program = coin_token_program,
+        constraint = coin_mint.key() == vault_0_mint.key() ||
+            coin_mint.key() == vault_1_mint.key() @ OrbitlenError::InvalidRaydiumPoolAccount,
+    )]
+    pub coin_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [BANK_SEED.as_bytes(), coin_mint.key().as_ref()],
+        bump
+    )]
+    pub coin_bank: AccountLoader<'info, Bank>,
+    /// CHECK: Seed constraint check
+    #[account(
+        mut,
+        seeds = [
+            LIQUIDITY_VAULT_SEED.as_bytes(),
+            coin_bank.key().as_ref(),
+        ],
+        bump = coin_bank.load()?.liquidity_vault_bump,
+    )]
+    pub coin_bank_liquidity_vault: AccountInfo<'info>,
+    /// CHECK: Seed constraint check
+    #[account(
+        seeds = [
+            LIQUIDITY_VAULT_AUTHORITY_SEED.as_bytes(),
+            coin_bank.key().as_ref(),
+        ],
+        bump = coin_bank.load()?.liquidity_vault_authority_bump,
+    )]
+    pub coin_bank_liquidity_vault_authority: AccountInfo<'info>,
+    /// Token program of `coin_mint`, used for bank vault transfers
+    pub coin_token_program: Interface<'info, TokenInterface>,
+    /**
+     * Optional pc bank, when set the other pool token is settled into it as well
+     */
+    #[account(mut)]
+    pub pc_bank: Option<AccountLoader<'info, Bank>>,
+    /// CHECK: Checked against pc_bank
+    #[account(mut)]
+    pub pc_bank_liquidity_vault: Option<AccountInfo<'info>>,
+    /// CHECK: Checked against pc_bank
+    pub pc_bank_liquidity_vault_authority: Option<AccountInfo<'info>>,
+    pub pc_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// Token program of `pc_mint`, used for bank vault transfers
+    pub pc_token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+impl<'info> ProxyClmmDecreaseLiquidity<'info> {
+    fn is_coin_token_0(&self) -> bool {
+        self.coin_mint.key() == self.vault_0_mint.key()
+    }
+}
+
+impl<'info> DexAdapter<'info> for ProxyClmmDecreaseLiquidity<'info> {
+    fn get_orbitlen_account(&self) -> &AccountLoader<'info, OrbitlenAccount> {
+        &self.orbitlen_account
+    }
+
+    fn get_user_owner(&self) -> AccountInfo<'info> {
+        self.owner.to_account_info()
+    }
+
+    fn get_coin_leg(&self) -> BankLeg<'_, 'info> {
+        BankLeg {
+            bank: &self.coin_bank,
+            bank_liquidity_vault: self.coin_bank_liquidity_vault.to_account_info(),
+            bank_liquidity_vault_authority: self.coin_bank_liquidity_vault_authority.to_account_info(),
+            mint: &self.coin_mint,
+            token_program: self.coin_token_program.to_account_info(),
+            user_token_account: if self.is_coin_token_0() {
+                self.token_account_0.to_account_info()
+            } else {
+                self.token_account_1.to_account_info()
+            },
+        }
+    }
+
+    fn get_pc_leg(&self) -> Result<Option<BankLeg<'_, 'info>>> {
+        let pc_leg = BankLeg::try_from_optional(
+            self.pc_bank.as_ref(),
+            self.pc_bank_liquidity_vault.as_ref(),
+            self.pc_bank_liquidity_vault_authority.as_ref(),
+            self.pc_mint.as_deref(),
+            self.pc_token_program.as_ref(),
+            Some(
+                if self.is_coin_token_0() {
+                    self.token_account_1.to_account_info()
+                } else {
+                    self.token_account_0.to_account_info()
+                }
+            )
+        )?;
+
+        if let Some(pc_leg) = &pc_leg {
+            require_keys_neq!(
+                pc_leg.mint.key(),
+                self.coin_mint.key(),
+                OrbitlenError::InvalidRaydiumPoolAccount
+            );
+            require!(
+                pc_leg.mint.key() == self.vault_0_mint.key() ||
+                    pc_leg.mint.key() == self.vault_1_mint.key(),
+                OrbitlenError::InvalidRaydiumPoolAccount
+            );
+        }
+
+        Ok(pc_leg)
+    }
+}
+
+/// Pulls `liquidity` out of the position, together with the fees owed to it, and deposits
+/// what the pool pays out into the banks.
+///
+/// Positions are not valued as collateral, so this only ever raises the account's health and
+/// runs no health check. An account under its maintenance requirement can still unwind its
+/// positions this way and close them with `raydium_clmm_close_position`.
+///
+/// Remaining accounts after the health accounts are forwarded to the CLMM program, which
+/// expects the reward vault, user reward token account and reward mint of each initialized
+/// pool reward. Rewards are paid to the user token accounts and not booked in a bank.
+pub fn clmm_decrease_liquidity_process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ProxyClmmDecreaseLiquidity<'info>>,
+    liquidity: u128,
+    amount_0_min: u64,
+    amount_1_min: u64
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+    let (_, reward_accounts) = accounts.split_health_accounts(ctx.remaining_accounts)?;
+
+    // both legs only grow here, so settling them deposits what the pool paid out
+    accounts.settle_cpi(ctx.remaining_accounts, || {
+        // pool vaults --> user token_0/token_1 accounts
+        let orbitlen_account_key = accounts.orbitlen_account.key();
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[
+                CLMM_POSITION_AUTHORITY_SEED.as_bytes(),
+                orbitlen_account_key.as_ref(),
+                &[ctx.bumps.position_authority],
+            ],
+        ];
+
+        let cpi_accounts = cpi::accounts::DecreaseLiquidityV2 {
+            nft_owner: accounts.position_authority.to_account_info(),
+            nft_account: accounts.position_nft_account.to_account_info(),
+            personal_position: accounts.personal_position.to_account_info(),
+            pool_state: accounts.pool_state.to_account_info(),
+            protocol_position: accounts.protocol_position.to_account_info(),
+            token_vault_0: accounts.token_vault_0.to_account_info(),
+            token_vault_1: accounts.token_vault_1.to_account_info(),
+            tick_array_lower: accounts.tick_array_lower.to_account_info(),
+            tick_array_upper: accounts.tick_array_upper.to_account_info(),
+            recipient_token_account_0: accounts.token_account_0.to_account_info(),
+            recipient_token_account_1: accounts.token_account_1.to_account_info(),
+            token_program: accounts.token_program.to_account_info(),
+            token_program_2022: accounts.token_program_2022.to_account_info(),
+            memo_program: accounts.memo_program.to_account_info(),
+            vault_0_mint: accounts.vault_0_mint.to_account_info(),
+            vault_1_mint: accounts.vault_1_mint.to_account_info(),
+        };
+        let cpi_program = accounts.clmm_program.to_account_info();
+        let cpi_ctx = CpiContext::new_with_signer(
+            cpi_program,
+            cpi_accounts,
+            signer_seeds
+        ).with_remaining_accounts(reward_accounts.to_vec());
+        cpi::decrease_liquidity_v2(cpi_ctx, liquidity, amount_0_min, amount_1_min)
+    })?;
+
+    Ok(())
+}
+
+/// Decreasing by zero liquidity only collects the fees owed to the position
+pub fn clmm_collect_fees_process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ProxyClmmDecreaseLiquidity<'info>>
+) -> Result<()> {
+    clmm_decrease_liquidity_process(ctx, 0, 0, 0)
+}
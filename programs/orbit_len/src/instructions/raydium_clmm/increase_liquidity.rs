@@ -0,0 +1,273 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::Token,
+    token_2022::Token2022,
+    token_interface::{ approve, revoke, Approve, Mint, Revoke, TokenInterface },
+};
+use raydium_clmm_cpi::{ cpi, program::AmmV3, states::PoolState };
+use crate::{ constants::*, bank::*, account::*, error::* };
+use crate::instructions::dex_adapter::*;
+
+#[derive(Accounts)]
+pub struct ProxyClmmIncreaseLiquidity<'info> {
+    /**
+     * Raydium CLMM accounts
+     */
+    pub clmm_program: Program<'info, AmmV3>,
+    /// CHECK: Safe. Position nft account of `position_authority`, checked by the CLMM program
+    pub position_nft_account: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+    /// CHECK: Safe. Protocol position account, checked by the CLMM program
+    #[account(mut)]
+    pub protocol_position: UncheckedAccount<'info>,
+    /// CHECK: Safe. Tick array of the lower tick, checked by the CLMM program
+    #[account(mut)]
+    pub tick_array_lower: UncheckedAccount<'info>,
+    /// CHECK: Safe. Tick array of the upper tick, checked by the CLMM program
+    #[account(mut)]
+    pub tick_array_upper: UncheckedAccount<'info>,
+    /// CHECK: Safe. Personal position account, checked by the CLMM program
+    #[account(mut)]
+    pub personal_position: UncheckedAccount<'info>,
+    /// CHECK: Safe. User token_0 account to pay from, checked by the CLMM program
+    #[account(mut)]
+    pub token_account_0: UncheckedAccount<'info>,
+    /// CHECK: Safe. User token_1 account to pay from, checked by the CLMM program
+    #[account(mut)]
+    pub token_account_1: UncheckedAccount<'info>,
+    /// CHECK: Address constraint check
+    #[account(mut, address = pool_state.load()?.token_vault_0)]
+    pub token_vault_0: UncheckedAccount<'info>,
+    /// CHECK: Address constraint check
+    #[account(mut, address = pool_state.load()?.token_vault_1)]
+    pub token_vault_1: UncheckedAccount<'info>,
+    #[account(address = pool_state.load()?.token_mint_0)]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = pool_state.load()?.token_mint_1)]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub token_program: Program<'info, Token>,
+    pub token_program_2022: Program<'info, Token2022>,
+    /**
+     * Orbitlen accounts
+     */
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [ORBITLEN_ACCOUNT_SEED.as_bytes(), owner.key().as_ref()],
+        bump,
+    )]
+    pub orbitlen_account: AccountLoader<'info, OrbitlenAccount>,
+    /// CHECK: Seed constraint check. Holds the position nft on behalf of `orbitlen_account`
+    #[account(
+        seeds = [CLMM_POSITION_AUTHORITY_SEED.as_bytes(), orbitlen_account.key().as_ref()],
+        bump,
+    )]
+    pub position_authority: AccountInfo<'info>,
+    #[account(
+        mint::decimals = coin_bank.load()?.mint_decimals,
+        mint::token_program = coin_token_program,
+        constraint = coin_mint.key() == vault_0_mint.key() ||
+            coin_mint.key() == vault_1_mint.key() @ OrbitlenError::InvalidRaydiumPoolAccount,
+    )]
+    pub coin_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [BANK_SEED.as_bytes(), coin_mint.key().as_ref()],
+        bump
+    )]
+    pub coin_bank: AccountLoader<'info, Bank>,
+    /// CHECK: Seed constraint check
+    #[account(
+        mut,
+        seeds = [
+            LIQUIDITY_VAULT_SEED.as_bytes(),
+            coin_bank.key().as_ref(),
+        ],
+        bump = coin_bank.load()?.liquidity_vault_bump,
+    )]
+    pub coin_bank_liquidity_vault: AccountInfo<'info>,
+    /// CHECK: Seed constraint check
+    #[account(
+        seeds = [
+            LIQUIDITY_VAULT_AUTHORITY_SEED.as_bytes(),
+            coin_bank.key().as_ref(),
+        ],
+        bump = coin_bank.load()?.liquidity_vault_authority_bump,
+    )]
+    pub coin_bank_liquidity_vault_authority: AccountInfo<'info>,
+    /// Token program of `coin_mint`, used for bank vault transfers
+    pub coin_token_program: Interface<'info, TokenInterface>,
+    /**
+     * Optional pc bank, when set the other pool token is sourced from and settled into it as well
+     */
+    #[account(mut)]
+    pub pc_bank: Option<AccountLoader<'info, Bank>>,
+    /// CHECK: Checked against pc_bank
+    #[account(mut)]
+    pub pc_bank_liquidity_vault: Option<AccountInfo<'info>>,
+    /// CHECK: Checked against pc_bank
+    pub pc_bank_liquidity_vault_authority: Option<AccountInfo<'info>>,
+    pub pc_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// Token program of `pc_mint`, used for bank vault transfers
+    pub pc_token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+impl<'info> ProxyClmmIncreaseLiquidity<'info> {
+    fn is_coin_token_0(&self) -> bool {
+        self.coin_mint.key() == self.vault_0_mint.key()
+    }
+
+    /// Token program of a pool mint, for the approvals of the user token accounts
+    fn get_mint_token_program(&self, mint: &InterfaceAccount<'info, Mint>) -> AccountInfo<'info> {
+        if *mint.to_account_info().owner == self.token_program_2022.key() {
+            self.token_program_2022.to_account_info()
+        } else {
+            self.token_program.to_account_info()
+        }
+    }
+}
+
+impl<'info> DexAdapter<'info> for ProxyClmmIncreaseLiquidity<'info> {
+    fn get_orbitlen_account(&self) -> &AccountLoader<'info, OrbitlenAccount> {
+        &self.orbitlen_account
+    }
+
+    fn get_user_owner(&self) -> AccountInfo<'info> {
+        self.owner.to_account_info()
+    }
+
+    fn get_coin_leg(&self) -> BankLeg<'_, 'info> {
+        BankLeg {
+            bank: &self.coin_bank,
+            bank_liquidity_vault: self.coin_bank_liquidity_vault.to_account_info(),
+            bank_liquidity_vault_authority: self.coin_bank_liquidity_vault_authority.to_account_info(),
+            mint: &self.coin_mint,
+            token_program: self.coin_token_program.to_account_info(),
+            user_token_account: if self.is_coin_token_0() {
+                self.token_account_0.to_account_info()
+            } else {
+                self.token_account_1.to_account_info()
+            },
+        }
+    }
+
+    fn get_pc_leg(&self) -> Result<Option<BankLeg<'_, 'info>>> {
+        let pc_leg = BankLeg::try_from_optional(
+            self.pc_bank.as_ref(),
+            self.pc_bank_liquidity_vault.as_ref(),
+            self.pc_bank_liquidity_vault_authority.as_ref(),
+            self.pc_mint.as_deref(),
+            self.pc_token_program.as_ref(),
+            self.get_user_pc_token_account()
+        )?;
+
+        if let Some(pc_leg) = &pc_leg {
+            require_keys_neq!(
+                pc_leg.mint.key(),
+                self.coin_mint.key(),
+                OrbitlenError::InvalidRaydiumPoolAccount
+            );
+            require!(
+                pc_leg.mint.key() == self.vault_0_mint.key() ||
+                    pc_leg.mint.key() == self.vault_1_mint.key(),
+                OrbitlenError::InvalidRaydiumPoolAccount
+            );
+        }
+
+        Ok(pc_leg)
+    }
+
+    fn get_user_pc_token_account(&self) -> Option<AccountInfo<'info>> {
+        Some(
+            if self.is_coin_token_0() {
+                self.token_account_1.to_account_info()
+            } else {
+                self.token_account_0.to_account_info()
+            }
+        )
+    }
+}
+
+pub fn clmm_increase_liquidity_process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ProxyClmmIncreaseLiquidity<'info>>,
+    liquidity: u128,
+    amount_0_max: u64,
+    amount_1_max: u64
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    let (coin_amount, pc_amount) = if accounts.is_coin_token_0() {
+        (amount_0_max, amount_1_max)
+    } else {
+        (amount_1_max, amount_0_max)
+    };
+
+    // whatever was borrowed but not added to the position goes straight back to the bank
+    accounts.borrow_for_cpi(coin_amount, pc_amount, ctx.remaining_accounts, || {
+        // the CLMM program pulls the liquidity from the position nft owner, so `position_authority`
+        // is approved on the user token accounts for the duration of the CPI
+        let token_approvals = [
+            (&accounts.token_account_0, &accounts.vault_0_mint, amount_0_max),
+            (&accounts.token_account_1, &accounts.vault_1_mint, amount_1_max),
+        ];
+
+        for (token_account, mint, amount) in token_approvals {
+            approve(
+                CpiContext::new(accounts.get_mint_token_program(mint), Approve {
+                    to: token_account.to_account_info(),
+                    delegate: accounts.position_authority.to_account_info(),
+                    authority: accounts.owner.to_account_info(),
+                }),
+                amount
+            )?;
+        }
+
+        {
+            // user token_0/token_1 accounts --> pool vaults
+            let orbitlen_account_key = accounts.orbitlen_account.key();
+            let signer_seeds: &[&[&[u8]]] = &[
+                &[
+                    CLMM_POSITION_AUTHORITY_SEED.as_bytes(),
+                    orbitlen_account_key.as_ref(),
+                    &[ctx.bumps.position_authority],
+                ],
+            ];
+
+            let cpi_accounts = cpi::accounts::IncreaseLiquidityV2 {
+                nft_owner: accounts.position_authority.to_account_info(),
+                nft_account: accounts.position_nft_account.to_account_info(),
+                pool_state: accounts.pool_state.to_account_info(),
+                protocol_position: accounts.protocol_position.to_account_info(),
+                personal_position: accounts.personal_position.to_account_info(),
+                tick_array_lower: accounts.tick_array_lower.to_account_info(),
+                tick_array_upper: accounts.tick_array_upper.to_account_info(),
+                token_account_0: accounts.token_account_0.to_account_info(),
+                token_account_1: accounts.token_account_1.to_account_info(),
+                token_vault_0: accounts.token_vault_0.to_account_info(),
+                token_vault_1: accounts.token_vault_1.to_account_info(),
+                token_program: accounts.token_program.to_account_info(),
+                token_program_2022: accounts.token_program_2022.to_account_info(),
+                vault_0_mint: accounts.vault_0_mint.to_account_info(),
+                vault_1_mint: accounts.vault_1_mint.to_account_info(),
+            };
+            let cpi_program = accounts.clmm_program.to_account_info();
+            let cpi_ctx = CpiContext::new_with_signer(cpi_program, cpi_accounts, signer_seeds);
+            cpi::increase_liquidity_v2(cpi_ctx, liquidity, amount_0_max, amount_1_max, None)?;
+        }
+
+        for (token_account, mint, _) in token_approvals {
+            revoke(
+                CpiContext::new(accounts.get_mint_token_program(mint), Revoke {
+                    source: token_account.to_account_info(),
+                    authority: accounts.owner.to_account_info(),
+                })
+            )?;
+        }
+
+        Ok(())
+    })?;
+
+    accounts.check_health(ctx.remaining_accounts)
+}
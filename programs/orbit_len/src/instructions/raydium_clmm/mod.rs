@@ -0,0 +1,9 @@
+mod open_position;
+mod increase_liquidity;
+mod decrease_liquidity;
+mod close_position;
+
+pub use open_position::*;
+pub use increase_liquidity::*;
+pub use decrease_liquidity::*;
+pub use close_position::*;
@@ -0,0 +1,299 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::{ transfer_checked, Token, TransferChecked },
+    token_2022::Token2022,
+    token_interface::{ Mint, TokenInterface },
+};
+use raydium_cpmm_cpi::{ cpi, program::RaydiumCpmm, states::PoolState };
+use crate::{ constants::*, bank::*, account::*, error::*, events::*, utils };
+use crate::instructions::dex_adapter::*;
+
+#[derive(Accounts)]
+pub struct ProxyCpmmDeposit<'info> {
+    /**
+     * Raydium CPMM accounts
+     */
+    pub cp_swap_program: Program<'info, RaydiumCpmm>,
+    /// CHECK: Seed constraint check. Pool vault and lp mint authority
+    #[account(
+        seeds = [raydium_cpmm_cpi::AUTH_SEED.as_bytes()],
+        seeds::program = cp_swap_program.key(),
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+    /// CHECK: Safe. User lp token account to mint into, checked by the CPMM program
+    #[account(mut)]
+    pub owner_lp_token: UncheckedAccount<'info>,
+    /// CHECK: Safe. User token_0 account to deposit from, checked by the CPMM program
+    #[account(mut)]
+    pub token_0_account: UncheckedAccount<'info>,
+    /// CHECK: Safe. User token_1 account to deposit from, checked by the CPMM program
+    #[account(mut)]
+    pub token_1_account: UncheckedAccount<'info>,
+    /// CHECK: Address constraint check
+    #[account(mut, address = pool_state.load()?.token_0_vault)]
+    pub token_0_vault: UncheckedAccount<'info>,
+    /// CHECK: Address constraint check
+    #[account(mut, address = pool_state.load()?.token_1_vault)]
+    pub token_1_vault: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub token_program_2022: Program<'info, Token2022>,
+    #[account(address = pool_state.load()?.token_0_mint)]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = pool_state.load()?.token_1_mint)]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, address = pool_state.load()?.lp_mint)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    /**
+     * Orbitlen accounts
+     */
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [ORBITLEN_ACCOUNT_SEED.as_bytes(), owner.key().as_ref()],
+        bump,
+    )]
+    pub orbitlen_account: AccountLoader<'info, OrbitlenAccount>,
+    #[account(
+        mint::decimals = coin_bank.load()?.mint_decimals,
+        mint::token_program = coin_token_program,
+        constraint = coin_mint.key() == vault_0_mint.key() ||
+            coin_mint.key() == vault_1_mint.key() @ OrbitlenError::InvalidRaydiumPoolAccount,
+    )]
+    pub coin_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [BANK_SEED.as_bytes(), coin_mint.key().as_ref()],
+        bump
+    )]
+    pub coin_bank: AccountLoader<'info, Bank>,
+    /// CHECK: Seed constraint check
+    #[account(
+        mut,
+        seeds = [
+            LIQUIDITY_VAULT_SEED.as_bytes(),
+            coin_bank.key().as_ref(),
+        ],
+        bump = coin_bank.load()?.liquidity_vault_bump,
+    )]
+    pub coin_bank_liquidity_vault: AccountInfo<'info>,
+    /// CHECK: Seed constraint check
+    #[account(
+        seeds = [
+            LIQUIDITY_VAULT_AUTHORITY_SEED.as_bytes(),
+            coin_bank.key().as_ref(),
+        ],
+        bump = coin_bank.load()?.liquidity_vault_authority_bump,
+    )]
+    pub coin_bank_liquidity_vault_authority: AccountInfo<'info>,
+    /// Token program of `coin_mint`, used for bank vault transfers
+    pub coin_token_program: Interface<'info, TokenInterface>,
+    #[account(
+        mut,
+        seeds = [BANK_SEED.as_bytes(), lp_mint.key().as_ref()],
+        bump,
+        constraint = lp_bank.load()?.config.raydium_cpmm_lp_config.pool_state == pool_state.key() @ OrbitlenError::InvalidRaydiumPoolAccount,
+    )]
+    pub lp_bank: AccountLoader<'info, Bank>,
+    /// CHECK: Seed constraint check
+    #[account(
+        mut,
+        seeds = [
+            LIQUIDITY_VAULT_SEED.as_bytes(),
+            lp_bank.key().as_ref(),
+        ],
+        bump = lp_bank.load()?.liquidity_vault_bump,
+    )]
+    pub lp_bank_liquidity_vault: AccountInfo<'info>,
+    /**
+     * Optional pc bank, when set the other pool token is sourced from and settled into it as well
+     */
+    #[account(mut)]
+    pub pc_bank: Option<AccountLoader<'info, Bank>>,
+    /// CHECK: Checked against pc_bank
+    #[account(mut)]
+    pub pc_bank_liquidity_vault: Option<AccountInfo<'info>>,
+    /// CHECK: Checked against pc_bank
+    pub pc_bank_liquidity_vault_authority: Option<AccountInfo<'info>>,
+    pub pc_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// Token program of `pc_mint`, used for bank vault transfers
+    pub pc_token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+impl<'info> ProxyCpmmDeposit<'info> {
+    fn is_coin_token_0(&self) -> bool {
+        self.coin_mint.key() == self.vault_0_mint.key()
+    }
+}
+
+impl<'info> DexAdapter<'info> for ProxyCpmmDeposit<'info> {
+    fn get_orbitlen_account(&self) -> &AccountLoader<'info, OrbitlenAccount> {
+        &self.orbitlen_account
+    }
+
+    fn get_user_owner(&self) -> AccountInfo<'info> {
+        self.owner.to_account_info()
+    }
+
+    fn get_coin_leg(&self) -> BankLeg<'_, 'info> {
+        BankLeg {
+            bank: &self.coin_bank,
+            bank_liquidity_vault: self.coin_bank_liquidity_vault.to_account_info(),
+            bank_liquidity_vault_authority: self.coin_bank_liquidity_vault_authority.to_account_info(),
+            mint: &self.coin_mint,
+            token_program: self.coin_token_program.to_account_info(),
+            user_token_account: if self.is_coin_token_0() {
+                self.token_0_account.to_account_info()
+            } else {
+                self.token_1_account.to_account_info()
+            },
+        }
+    }
+
+    fn get_pc_leg(&self) -> Result<Option<BankLeg<'_, 'info>>> {
+        let pc_leg = BankLeg::try_from_optional(
+            self.pc_bank.as_ref(),
+            self.pc_bank_liquidity_vault.as_ref(),
+            self.pc_bank_liquidity_vault_authority.as_ref(),
+            self.pc_mint.as_deref(),
+            self.pc_token_program.as_ref(),
+            self.get_user_pc_token_account()
+        )?;
+
+        if let Some(pc_leg) = &pc_leg {
+            require_keys_neq!(
+                pc_leg.mint.key(),
+                self.coin_mint.key(),
+                OrbitlenError::InvalidRaydiumPoolAccount
+            );
+            require!(
+                pc_leg.mint.key() == self.vault_0_mint.key() ||
+                    pc_leg.mint.key() == self.vault_1_mint.key(),
+                OrbitlenError::InvalidRaydiumPoolAccount
+            );
+        }
+
+        Ok(pc_leg)
+    }
+
+    fn get_user_pc_token_account(&self) -> Option<AccountInfo<'info>> {
+        Some(
+            if self.is_coin_token_0() {
+                self.token_1_account.to_account_info()
+            } else {
+                self.token_0_account.to_account_info()
+            }
+        )
+    }
+
+    fn get_lp_bank(&self) -> Option<Pubkey> {
+        Some(self.lp_bank.key())
+    }
+}
+
+pub fn cpmm_deposit_process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ProxyCpmmDeposit<'info>>,
+    lp_token_amount: u64,
+    maximum_token_0_amount: u64,
+    maximum_token_1_amount: u64
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    let (coin_amount, pc_amount) = if accounts.is_coin_token_0() {
+        (maximum_token_0_amount, maximum_token_1_amount)
+    } else {
+        (maximum_token_1_amount, maximum_token_0_amount)
+    };
+
+    let lp_amount_before = utils::get_token_account_amount(&accounts.owner_lp_token)?;
+
+    // the pool takes what `lp_token_amount` is worth, up to the maximum amounts, whatever
+    // was borrowed but not deposited goes straight back to the bank
+    let (coin_amount_used, pc_amount_used) = accounts.borrow_for_cpi(
+        coin_amount,
+        pc_amount,
+        ctx.remaining_accounts,
+        || {
+            // user token_0/token_1 accounts --> pool vaults
+            let cpi_accounts = cpi::accounts::Deposit {
+                owner: accounts.owner.to_account_info(),
+                authority: accounts.authority.to_account_info(),
+                pool_state: accounts.pool_state.to_account_info(),
+                owner_lp_token: accounts.owner_lp_token.to_account_info(),
+                token_0_account: accounts.token_0_account.to_account_info(),
+                token_1_account: accounts.token_1_account.to_account_info(),
+                token_0_vault: accounts.token_0_vault.to_account_info(),
+                token_1_vault: accounts.token_1_vault.to_account_info(),
+                token_program: accounts.token_program.to_account_info(),
+                token_program_2022: accounts.token_program_2022.to_account_info(),
+                vault_0_mint: accounts.vault_0_mint.to_account_info(),
+                vault_1_mint: accounts.vault_1_mint.to_account_info(),
+                lp_mint: accounts.lp_mint.to_account_info(),
+            };
+            let cpi_program = accounts.cp_swap_program.to_account_info();
+            let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+            cpi::deposit(cpi_ctx, lp_token_amount, maximum_token_0_amount, maximum_token_1_amount)
+        }
+    )?;
+
+    let lp_amount = utils::get_token_account_amount(&accounts.owner_lp_token)?
+        .checked_sub(lp_amount_before)
+        .ok_or(OrbitlenError::MathError)?;
+
+    debug!("lp_amount: {}", lp_amount);
+
+    {
+        // user lp token account --> lp bank vault, escrowed as collateral of the user
+        let clock = Clock::get().map_err(|_| OrbitlenError::GetClockFailed)?;
+
+        let mut lp_bank = accounts.lp_bank.load_mut()?;
+        let lp_mint_decimals = lp_bank.mint_decimals;
+
+        let mut orbitlen_account = accounts.orbitlen_account.load_mut()?;
+
+        lp_bank.accrue_interest(clock.unix_timestamp, &accounts.lp_bank.key())?;
+
+        let mut lp_bank_account = BankAccountWrapper::find_or_create(
+            &accounts.lp_bank.key(),
+            &mut lp_bank,
+            &mut orbitlen_account.lending_account
+        )?;
+
+        lp_bank_account.deposit(lp_amount)?;
+
+        transfer_checked(
+            CpiContext::new(accounts.token_program.to_account_info(), TransferChecked {
+                from: accounts.owner_lp_token.to_account_info(),
+                mint: accounts.lp_mint.to_account_info(),
+                to: accounts.lp_bank_liquidity_vault.to_account_info(),
+                authority: accounts.owner.to_account_info(),
+            }),
+            lp_amount,
+            lp_mint_decimals
+        )?;
+    }
+
+    let (token_0_amount, token_1_amount) = if accounts.is_coin_token_0() {
+        (coin_amount_used, pc_amount_used)
+    } else {
+        (pc_amount_used, coin_amount_used)
+    };
+
+    emit!(RaydiumCpmmDepositEvent {
+        header: RaydiumCpmmEventHeader {
+            pool_state: accounts.pool_state.key(),
+            signer: accounts.owner.key(),
+            orbitlen_account: accounts.orbitlen_account.key(),
+            orbitlen_account_authority: accounts.orbitlen_account.load()?.authority,
+        },
+        coin_mint: accounts.coin_mint.key(),
+        token_0_amount,
+        token_1_amount,
+        lp_amount,
+    });
+
+    accounts.check_health(ctx.remaining_accounts)
+}
@@ -0,0 +1,7 @@
+mod deposit;
+mod withdraw;
+mod swap;
+
+pub use deposit::*;
+pub use withdraw::*;
+pub use swap::*;
@@ -0,0 +1,222 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{ Mint, TokenInterface };
+use raydium_cpmm_cpi::{ cpi, program::RaydiumCpmm, states::PoolState };
+use crate::{ constants::*, bank::*, account::*, error::* };
+use crate::instructions::dex_adapter::*;
+
+/// Shared by `raydium_cpmm_swap_base_input` and `raydium_cpmm_swap_base_output`, like the
+/// CPMM program's own `Swap` accounts.
+#[derive(Accounts)]
+pub struct ProxyCpmmSwap<'info> {
+    /**
+     * Raydium CPMM accounts
+     */
+    pub cp_swap_program: Program<'info, RaydiumCpmm>,
+    /// CHECK: Seed constraint check. Pool vault and lp mint authority
+    #[account(
+        seeds = [raydium_cpmm_cpi::AUTH_SEED.as_bytes()],
+        seeds::program = cp_swap_program.key(),
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+    /// CHECK: Address constraint check
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+    /// CHECK: Safe. User token account to swap from, checked by the CPMM program
+    #[account(mut)]
+    pub input_token_account: UncheckedAccount<'info>,
+    /// CHECK: Safe. User token account to swap to, checked by the CPMM program
+    #[account(mut)]
+    pub output_token_account: UncheckedAccount<'info>,
+    /// CHECK: Safe. Pool vault of the input token, checked by the CPMM program
+    #[account(mut)]
+    pub input_vault: UncheckedAccount<'info>,
+    /// CHECK: Safe. Pool vault of the output token, checked by the CPMM program
+    #[account(mut)]
+    pub output_vault: UncheckedAccount<'info>,
+    pub input_token_program: Interface<'info, TokenInterface>,
+    pub output_token_program: Interface<'info, TokenInterface>,
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: Address constraint check
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: UncheckedAccount<'info>,
+    /**
+     * Orbitlen accounts
+     */
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [ORBITLEN_ACCOUNT_SEED.as_bytes(), payer.key().as_ref()],
+        bump,
+    )]
+    pub orbitlen_account: AccountLoader<'info, OrbitlenAccount>,
+    #[account(
+        mint::decimals = coin_bank.load()?.mint_decimals,
+        mint::token_program = coin_token_program,
+        constraint = coin_mint.key() == input_token_mint.key() ||
+            coin_mint.key() == output_token_mint.key() @ OrbitlenError::InvalidRaydiumPoolAccount,
+    )]
+    pub coin_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [BANK_SEED.as_bytes(), coin_mint.key().as_ref()],
+        bump
+    )]
+    pub coin_bank: AccountLoader<'info, Bank>,
+    /// CHECK: Seed constraint check
+    #[account(
+        mut,
+        seeds = [
+            LIQUIDITY_VAULT_SEED.as_bytes(),
+            coin_bank.key().as_ref(),
+        ],
+        bump = coin_bank.load()?.liquidity_vault_bump,
+    )]
+    pub coin_bank_liquidity_vault: AccountInfo<'info>,
+    /// CHECK: Seed constraint check
+    #[account(
+        seeds = [
+            LIQUIDITY_VAULT_AUTHORITY_SEED.as_bytes(),
+            coin_bank.key().as_ref(),
+        ],
+        bump = coin_bank.load()?.liquidity_vault_authority_bump,
+    )]
+    pub coin_bank_liquidity_vault_authority: AccountInfo<'info>,
+    /// Token program of `coin_mint`, used for bank vault transfers
+    pub coin_token_program: Interface<'info, TokenInterface>,
+    /**
+     * Optional pc bank, when set the other side of the swap is settled into it as well
+     */
+    #[account(mut)]
+    pub pc_bank: Option<AccountLoader<'info, Bank>>,
+    /// CHECK: Checked against pc_bank
+    #[account(mut)]
+    pub pc_bank_liquidity_vault: Option<AccountInfo<'info>>,
+    /// CHECK: Checked against pc_bank
+    pub pc_bank_liquidity_vault_authority: Option<AccountInfo<'info>>,
+    pub pc_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// Token program of `pc_mint`, used for bank vault transfers
+    pub pc_token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+impl<'info> ProxyCpmmSwap<'info> {
+    fn is_coin_input(&self) -> bool {
+        self.coin_mint.key() == self.input_token_mint.key()
+    }
+
+    fn to_cpi_accounts(&self) -> cpi::accounts::Swap<'info> {
+        cpi::accounts::Swap {
+            payer: self.payer.to_account_info(),
+            authority: self.authority.to_account_info(),
+            amm_config: self.amm_config.to_account_info(),
+            pool_state: self.pool_state.to_account_info(),
+            input_token_account: self.input_token_account.to_account_info(),
+            output_token_account: self.output_token_account.to_account_info(),
+            input_vault: self.input_vault.to_account_info(),
+            output_vault: self.output_vault.to_account_info(),
+            input_token_program: self.input_token_program.to_account_info(),
+            output_token_program: self.output_token_program.to_account_info(),
+            input_token_mint: self.input_token_mint.to_account_info(),
+            output_token_mint: self.output_token_mint.to_account_info(),
+            observation_state: self.observation_state.to_account_info(),
+        }
+    }
+}
+
+impl<'info> DexAdapter<'info> for ProxyCpmmSwap<'info> {
+    fn get_orbitlen_account(&self) -> &AccountLoader<'info, OrbitlenAccount> {
+        &self.orbitlen_account
+    }
+
+    fn get_user_owner(&self) -> AccountInfo<'info> {
+        self.payer.to_account_info()
+    }
+
+    fn get_coin_leg(&self) -> BankLeg<'_, 'info> {
+        BankLeg {
+            bank: &self.coin_bank,
+            bank_liquidity_vault: self.coin_bank_liquidity_vault.to_account_info(),
+            bank_liquidity_vault_authority: self.coin_bank_liquidity_vault_authority.to_account_info(),
+            mint: &self.coin_mint,
+            token_program: self.coin_token_program.to_account_info(),
+            user_token_account: if self.is_coin_input() {
+                self.input_token_account.to_account_info()
+            } else {
+                self.output_token_account.to_account_info()
+            },
+        }
+    }
+
+    fn get_pc_leg(&self) -> Result<Option<BankLeg<'_, 'info>>> {
+        let pc_leg = BankLeg::try_from_optional(
+            self.pc_bank.as_ref(),
+            self.pc_bank_liquidity_vault.as_ref(),
+            self.pc_bank_liquidity_vault_authority.as_ref(),
+            self.pc_mint.as_deref(),
+            self.pc_token_program.as_ref(),
+            Some(
+                if self.is_coin_input() {
+                    self.output_token_account.to_account_info()
+                } else {
+                    self.input_token_account.to_account_info()
+                }
+            )
+        )?;
+
+        if let Some(pc_leg) = &pc_leg {
+            require_keys_neq!(
+                pc_leg.mint.key(),
+                self.coin_mint.key(),
+                OrbitlenError::InvalidRaydiumPoolAccount
+            );
+            require!(
+                pc_leg.mint.key() == self.input_token_mint.key() ||
+                    pc_leg.mint.key() == self.output_token_mint.key(),
+                OrbitlenError::InvalidRaydiumPoolAccount
+            );
+        }
+
+        Ok(pc_leg)
+    }
+
+}
+
+pub fn cpmm_swap_base_input_process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ProxyCpmmSwap<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    accounts.settle_cpi(ctx.remaining_accounts, || {
+        let cpi_ctx = CpiContext::new(
+            accounts.cp_swap_program.to_account_info(),
+            accounts.to_cpi_accounts()
+        );
+        cpi::swap_base_input(cpi_ctx, amount_in, minimum_amount_out)
+    })?;
+
+    accounts.check_health(ctx.remaining_accounts)
+}
+
+pub fn cpmm_swap_base_output_process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ProxyCpmmSwap<'info>>,
+    max_amount_in: u64,
+    amount_out: u64
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    accounts.settle_cpi(ctx.remaining_accounts, || {
+        let cpi_ctx = CpiContext::new(
+            accounts.cp_swap_program.to_account_info(),
+            accounts.to_cpi_accounts()
+        );
+        cpi::swap_base_output(cpi_ctx, max_amount_in, amount_out)
+    })?;
+
+    accounts.check_health(ctx.remaining_accounts)
+}
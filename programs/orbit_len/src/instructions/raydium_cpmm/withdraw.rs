@@ -0,0 +1,284 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::{ transfer_checked, Token, TransferChecked },
+    token_2022::Token2022,
+    token_interface::{ Mint, TokenInterface },
+};
+use raydium_cpmm_cpi::{ cpi, program::RaydiumCpmm, states::PoolState };
+use crate::{ constants::*, bank::*, account::*, error::* };
+use crate::instructions::dex_adapter::*;
+
+#[derive(Accounts)]
+pub struct ProxyCpmmWithdraw<'info> {
+    /**
+     * Raydium CPMM accounts
+     */
+    pub cp_swap_program: Program<'info, RaydiumCpmm>,
+    /// CHECK: Seed constraint check. Pool vault and lp mint authority
+    #[account(
+        seeds = [raydium_cpmm_cpi::AUTH_SEED.as_bytes()],
+        seeds::program = cp_swap_program.key(),
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+    /// CHECK: Safe. User lp token account to burn from, checked by the CPMM program
+    #[account(mut)]
+    pub owner_lp_token: UncheckedAccount<'info>,
+    /// CHECK: Safe. User token_0 account to credit, checked by the CPMM program
+    #[account(mut)]
+    pub token_0_account: UncheckedAccount<'info>,
+    /// CHECK: Safe. User token_1 account to credit, checked by the CPMM program
+    #[account(mut)]
+    pub token_1_account: UncheckedAccount<'info>,
+    /// CHECK: Address constraint check
+    #[account(mut, address = pool_state.load()?.token_0_vault)]
+    pub token_0_vault: UncheckedAccount<'info>,
+    /// CHECK: Address constraint check
+    #[account(mut, address = pool_state.load()?.token_1_vault)]
+    pub token_1_vault: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub token_program_2022: Program<'info, Token2022>,
+    #[account(address = pool_state.load()?.token_0_mint)]
+    pub vault_0_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(address = pool_state.load()?.token_1_mint)]
+    pub vault_1_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(mut, address = pool_state.load()?.lp_mint)]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: Address constraint check
+    #[account(address = SPL_MEMO_PROGRAM_ID)]
+    pub memo_program: UncheckedAccount<'info>,
+    /**
+     * Orbitlen accounts
+     */
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [ORBITLEN_ACCOUNT_SEED.as_bytes(), owner.key().as_ref()],
+        bump,
+    )]
+    pub orbitlen_account: AccountLoader<'info, OrbitlenAccount>,
+    #[account(
+        mint::decimals = coin_bank.load()?.mint_decimals,
+        mint::token_program = coin_token_program,
+        constraint = coin_mint.key() == vault_0_mint.key() ||
+            coin_mint.key() == vault_1_mint.key() @ OrbitlenError::InvalidRaydiumPoolAccount,
+    )]
+    pub coin_mint: Box<InterfaceAccount<'info, Mint>>,
+    #[account(
+        mut,
+        seeds = [BANK_SEED.as_bytes(), coin_mint.key().as_ref()],
+        bump
+    )]
+    pub coin_bank: AccountLoader<'info, Bank>,
+    /// CHECK: Seed constraint check
+    #[account(
+        mut,
+        seeds = [
+            LIQUIDITY_VAULT_SEED.as_bytes(),
+            coin_bank.key().as_ref(),
+        ],
+        bump = coin_bank.load()?.liquidity_vault_bump,
+    )]
+    pub coin_bank_liquidity_vault: AccountInfo<'info>,
+    /// CHECK: Seed constraint check
+    #[account(
+        seeds = [
+            LIQUIDITY_VAULT_AUTHORITY_SEED.as_bytes(),
+            coin_bank.key().as_ref(),
+        ],
+        bump = coin_bank.load()?.liquidity_vault_authority_bump,
+    )]
+    pub coin_bank_liquidity_vault_authority: AccountInfo<'info>,
+    /// Token program of `coin_mint`, used for bank vault transfers
+    pub coin_token_program: Interface<'info, TokenInterface>,
+    #[account(
+        mut,
+        seeds = [BANK_SEED.as_bytes(), lp_mint.key().as_ref()],
+        bump,
+        constraint = lp_bank.load()?.config.raydium_cpmm_lp_config.pool_state == pool_state.key() @ OrbitlenError::InvalidRaydiumPoolAccount,
+    )]
+    pub lp_bank: AccountLoader<'info, Bank>,
+    /// CHECK: Seed constraint check
+    #[account(
+        mut,
+        seeds = [
+            LIQUIDITY_VAULT_SEED.as_bytes(),
+            lp_bank.key().as_ref(),
+        ],
+        bump = lp_bank.load()?.liquidity_vault_bump,
+    )]
+    pub lp_bank_liquidity_vault: AccountInfo<'info>,
+    /// CHECK: Seed constraint check
+    #[account(
+        seeds = [
+            LIQUIDITY_VAULT_AUTHORITY_SEED.as_bytes(),
+            lp_bank.key().as_ref(),
+        ],
+        bump = lp_bank.load()?.liquidity_vault_authority_bump,
+    )]
+    pub lp_bank_liquidity_vault_authority: AccountInfo<'info>,
+    /**
+     * Optional pc bank, when set the other pool token is settled into it as well
+     */
+    #[account(mut)]
+    pub pc_bank: Option<AccountLoader<'info, Bank>>,
+    /// CHECK: Checked against pc_bank
+    #[account(mut)]
+    pub pc_bank_liquidity_vault: Option<AccountInfo<'info>>,
+    /// CHECK: Checked against pc_bank
+    pub pc_bank_liquidity_vault_authority: Option<AccountInfo<'info>>,
+    pub pc_mint: Option<Box<InterfaceAccount<'info, Mint>>>,
+    /// Token program of `pc_mint`, used for bank vault transfers
+    pub pc_token_program: Option<Interface<'info, TokenInterface>>,
+}
+
+impl<'info> ProxyCpmmWithdraw<'info> {
+    fn is_coin_token_0(&self) -> bool {
+        self.coin_mint.key() == self.vault_0_mint.key()
+    }
+}
+
+impl<'info> DexAdapter<'info> for ProxyCpmmWithdraw<'info> {
+    fn get_orbitlen_account(&self) -> &AccountLoader<'info, OrbitlenAccount> {
+        &self.orbitlen_account
+    }
+
+    fn get_user_owner(&self) -> AccountInfo<'info> {
+        self.owner.to_account_info()
+    }
+
+    fn get_coin_leg(&self) -> BankLeg<'_, 'info> {
+        BankLeg {
+            bank: &self.coin_bank,
+            bank_liquidity_vault: self.coin_bank_liquidity_vault.to_account_info(),
+            bank_liquidity_vault_authority: self.coin_bank_liquidity_vault_authority.to_account_info(),
+            mint: &self.coin_mint,
+            token_program: self.coin_token_program.to_account_info(),
+            user_token_account: if self.is_coin_token_0() {
+                self.token_0_account.to_account_info()
+            } else {
+                self.token_1_account.to_account_info()
+            },
+        }
+    }
+
+    fn get_pc_leg(&self) -> Result<Option<BankLeg<'_, 'info>>> {
+        let pc_leg = BankLeg::try_from_optional(
+            self.pc_bank.as_ref(),
+            self.pc_bank_liquidity_vault.as_ref(),
+            self.pc_bank_liquidity_vault_authority.as_ref(),
+            self.pc_mint.as_deref(),
+            self.pc_token_program.as_ref(),
+            Some(
+                if self.is_coin_token_0() {
+                    self.token_1_account.to_account_info()
+                } else {
+                    self.token_0_account.to_account_info()
+                }
+            )
+        )?;
+
+        if let Some(pc_leg) = &pc_leg {
+            require_keys_neq!(
+                pc_leg.mint.key(),
+                self.coin_mint.key(),
+                OrbitlenError::InvalidRaydiumPoolAccount
+            );
+            require!(
+                pc_leg.mint.key() == self.vault_0_mint.key() ||
+                    pc_leg.mint.key() == self.vault_1_mint.key(),
+                OrbitlenError::InvalidRaydiumPoolAccount
+            );
+        }
+
+        Ok(pc_leg)
+    }
+
+    fn get_lp_bank(&self) -> Option<Pubkey> {
+        Some(self.lp_bank.key())
+    }
+}
+
+pub fn cpmm_withdraw_process<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ProxyCpmmWithdraw<'info>>,
+    lp_token_amount: u64,
+    minimum_token_0_amount: u64,
+    minimum_token_1_amount: u64
+) -> Result<()> {
+    let accounts = &ctx.accounts;
+
+    {
+        // lp bank vault --> user lp token account, the escrowed LP can only leave to be burned
+        let clock = Clock::get().map_err(|_| OrbitlenError::GetClockFailed)?;
+
+        let mut lp_bank = accounts.lp_bank.load_mut()?;
+        let lp_mint_decimals = lp_bank.mint_decimals;
+        let lp_liquidity_vault_authority_bump = lp_bank.liquidity_vault_authority_bump;
+
+        let mut orbitlen_account = accounts.orbitlen_account.load_mut()?;
+
+        lp_bank.accrue_interest(clock.unix_timestamp, &accounts.lp_bank.key())?;
+
+        let mut lp_bank_account = BankAccountWrapper::find(
+            &accounts.lp_bank.key(),
+            &mut lp_bank,
+            &mut orbitlen_account.lending_account
+        )?;
+
+        // LP banks are not borrowable, so this fails rather than open a liability
+        lp_bank_account.withdraw(lp_token_amount)?;
+
+        let signer_seeds: &[&[&[u8]]] = &[
+            &[
+                BankVaultType::Liquidity.get_authority_seed(),
+                &accounts.lp_bank.key().to_bytes(),
+                &[lp_liquidity_vault_authority_bump],
+            ],
+        ];
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: accounts.lp_bank_liquidity_vault.to_account_info(),
+                    mint: accounts.lp_mint.to_account_info(),
+                    to: accounts.owner_lp_token.to_account_info(),
+                    authority: accounts.lp_bank_liquidity_vault_authority.to_account_info(),
+                },
+                signer_seeds
+            ),
+            lp_token_amount,
+            lp_mint_decimals
+        )?;
+    }
+
+    // both legs only grow here, so settling them deposits what the pool paid out
+    accounts.settle_cpi(ctx.remaining_accounts, || {
+        // pool vaults --> user token_0/token_1 accounts, the minimum amounts are enforced by
+        // the CPMM program
+        let cpi_accounts = cpi::accounts::Withdraw {
+            owner: accounts.owner.to_account_info(),
+            authority: accounts.authority.to_account_info(),
+            pool_state: accounts.pool_state.to_account_info(),
+            owner_lp_token: accounts.owner_lp_token.to_account_info(),
+            token_0_account: accounts.token_0_account.to_account_info(),
+            token_1_account: accounts.token_1_account.to_account_info(),
+            token_0_vault: accounts.token_0_vault.to_account_info(),
+            token_1_vault: accounts.token_1_vault.to_account_info(),
+            token_program: accounts.token_program.to_account_info(),
+            token_program_2022: accounts.token_program_2022.to_account_info(),
+            vault_0_mint: accounts.vault_0_mint.to_account_info(),
+            vault_1_mint: accounts.vault_1_mint.to_account_info(),
+            lp_mint: accounts.lp_mint.to_account_info(),
+            memo_program: accounts.memo_program.to_account_info(),
+        };
+        let cpi_program = accounts.cp_swap_program.to_account_info();
+        let cpi_ctx = CpiContext::new(cpi_program, cpi_accounts);
+        cpi::withdraw(cpi_ctx, lp_token_amount, minimum_token_0_amount, minimum_token_1_amount)
+    })?;
+
+    accounts.check_health(ctx.remaining_accounts)
+}
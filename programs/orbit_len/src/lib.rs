@@ -1,3 +1,6 @@
+#[macro_use]
+mod macros;
+
 pub mod constants;
 pub mod error;
 pub mod instructions;
@@ -7,6 +10,8 @@ pub mod events;
 
 use anchor_lang::prelude::*;
 
+pub use orbit_len_math as math;
+
 pub use constants::*;
 pub use instructions::*;
 pub use state::*;
@@ -29,6 +34,27 @@ pub mod orbit_len {
     pub fn initial_vault(ctx: Context<InitialVault>, bank: Pubkey) -> Result<()> {
         initial_vault_process(ctx, bank)
     }
+    pub fn lending_pool_configure_emode_category(
+        ctx: Context<LendingPoolConfigureEmodeCategory>,
+        category: u8,
+        emode_config: EmodeConfigCompact
+    ) -> Result<()> {
+        lending_pool_configure_emode_category_process(ctx, category, emode_config.into())
+    }
+
+    // permissionless instructions
+    pub fn lending_pool_accrue_bank_interest(
+        ctx: Context<LendingPoolAccrueBankInterest>
+    ) -> Result<()> {
+        lending_pool_accrue_bank_interest_process(ctx)
+    }
+
+    pub fn lending_account_update_liquidation_auction<'info>(
+        ctx: Context<'_, '_, 'info, 'info, LendingAccountUpdateLiquidationAuction<'info>>
+    ) -> Result<()> {
+        lending_account_update_liquidation_auction_process(ctx)
+    }
+
     // user instructions
     pub fn initialize_account<'info>(
         ctx: Context<'_, '_, 'info, 'info, OrbitlenAccountInitialize<'info>>
@@ -50,6 +76,19 @@ pub mod orbit_len {
         lending_account_deposit_process(ctx, amount)
     }
 
+    pub fn lending_account_get_health<'info>(
+        ctx: Context<'_, '_, 'info, 'info, LendingAccountGetHealth<'info>>
+    ) -> Result<LendingAccountHealthReport> {
+        lending_account_get_health_process(ctx)
+    }
+
+    pub fn lending_account_set_emode<'info>(
+        ctx: Context<'_, '_, 'info, 'info, LendingAccountSetEmode<'info>>,
+        emode_category: u8
+    ) -> Result<()> {
+        lending_account_set_emode_process(ctx, emode_category)
+    }
+
     pub fn lending_account_liquidate<'info>(
         ctx: Context<'_, '_, 'info, 'info, LendingAccountLiquidate<'info>>,
         asset_amount: u64
@@ -57,24 +96,41 @@ pub mod orbit_len {
         lending_account_liquidate_process(ctx, asset_amount)
     }
 
+    pub fn lending_account_liquidate_multi<'info>(
+        ctx: Context<'_, '_, 'info, 'info, LendingAccountLiquidateMulti<'info>>,
+        asset_amounts: Vec<u64>
+    ) -> Result<()> {
+        lending_account_liquidate_multi_process(ctx, asset_amounts)
+    }
+
+    pub fn lending_account_liquidate_with_tokens<'info>(
+        ctx: Context<'_, '_, 'info, 'info, LendingAccountLiquidateWithTokens<'info>>,
+        asset_amount: u64
+    ) -> Result<()> {
+        lending_account_liquidate_with_tokens_process(ctx, asset_amount)
+    }
+
     // other defi protocols
     pub fn raydium_deposit<'info>(
-        ctx: Context<'_, '_, '_, 'info, ProxyDeposit<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, ProxyDeposit<'info>>,
         coin_amount: u64,
-        pc_amount: u64
+        pc_amount: u64,
+        min_lp_amount: u64
     ) -> Result<()> {
-        raydium::deposit_process(ctx, coin_amount, pc_amount)
+        raydium::deposit_process(ctx, coin_amount, pc_amount, min_lp_amount)
     }
 
     pub fn raydium_withdraw<'info>(
-        ctx: Context<'_, '_, '_, 'info, ProxyWithdraw<'info>>,
-        amount: u64
+        ctx: Context<'_, '_, 'info, 'info, ProxyWithdraw<'info>>,
+        amount: u64,
+        min_coin_amount: u64,
+        min_pc_amount: u64
     ) -> Result<()> {
-        raydium::withdraw_process(ctx, amount)
+        raydium::withdraw_process(ctx, amount, min_coin_amount, min_pc_amount)
     }
 
     pub fn raydium_swap_base_in<'info>(
-        ctx: Context<'_, '_, '_, 'info, ProxySwapBaseIn<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, ProxySwap<'info>>,
         amount_in: u64,
         minimum_amount_out: u64
     ) -> Result<()> {
@@ -82,10 +138,105 @@ pub mod orbit_len {
     }
 
     pub fn raydium_swap_base_out<'info>(
-        ctx: Context<'_, '_, '_, 'info, ProxySwapBaseOut<'info>>,
+        ctx: Context<'_, '_, 'info, 'info, ProxySwap<'info>>,
         max_amount_in: u64,
         amount_out: u64
     ) -> Result<()> {
         raydium::swap_base_out_process(ctx, max_amount_in, amount_out)
     }
+
+    pub fn raydium_cpmm_deposit<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProxyCpmmDeposit<'info>>,
+        lp_token_amount: u64,
+        maximum_token_0_amount: u64,
+        maximum_token_1_amount: u64
+    ) -> Result<()> {
+        raydium_cpmm::cpmm_deposit_process(
+            ctx,
+            lp_token_amount,
+            maximum_token_0_amount,
+            maximum_token_1_amount
+        )
+    }
+
+    pub fn raydium_cpmm_withdraw<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProxyCpmmWithdraw<'info>>,
+        lp_token_amount: u64,
+        minimum_token_0_amount: u64,
+        minimum_token_1_amount: u64
+    ) -> Result<()> {
+        raydium_cpmm::cpmm_withdraw_process(
+            ctx,
+            lp_token_amount,
+            minimum_token_0_amount,
+            minimum_token_1_amount
+        )
+    }
+
+    pub fn raydium_cpmm_swap_base_input<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProxyCpmmSwap<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64
+    ) -> Result<()> {
+        raydium_cpmm::cpmm_swap_base_input_process(ctx, amount_in, minimum_amount_out)
+    }
+
+    pub fn raydium_cpmm_swap_base_output<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProxyCpmmSwap<'info>>,
+        max_amount_in: u64,
+        amount_out: u64
+    ) -> Result<()> {
+        raydium_cpmm::cpmm_swap_base_output_process(ctx, max_amount_in, amount_out)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn raydium_clmm_open_position<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProxyClmmOpenPosition<'info>>,
+        tick_lower_index: i32,
+        tick_upper_index: i32,
+        tick_array_lower_start_index: i32,
+        tick_array_upper_start_index: i32,
+        liquidity: u128,
+        amount_0_max: u64,
+        amount_1_max: u64
+    ) -> Result<()> {
+        raydium_clmm::clmm_open_position_process(
+            ctx,
+            tick_lower_index,
+            tick_upper_index,
+            tick_array_lower_start_index,
+            tick_array_upper_start_index,
+            liquidity,
+            amount_0_max,
+            amount_1_max
+        )
+    }
+
+    pub fn raydium_clmm_increase_liquidity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProxyClmmIncreaseLiquidity<'info>>,
+        liquidity: u128,
+        amount_0_max: u64,
+        amount_1_max: u64
+    ) -> Result<()> {
+        raydium_clmm::clmm_increase_liquidity_process(ctx, liquidity, amount_0_max, amount_1_max)
+    }
+
+    pub fn raydium_clmm_decrease_liquidity<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProxyClmmDecreaseLiquidity<'info>>,
+        liquidity: u128,
+        amount_0_min: u64,
+        amount_1_min: u64
+    ) -> Result<()> {
+        raydium_clmm::clmm_decrease_liquidity_process(ctx, liquidity, amount_0_min, amount_1_min)
+    }
+
+    pub fn raydium_clmm_collect_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ProxyClmmDecreaseLiquidity<'info>>
+    ) -> Result<()> {
+        raydium_clmm::clmm_collect_fees_process(ctx)
+    }
+
+    pub fn raydium_clmm_close_position(ctx: Context<ProxyClmmClosePosition>) -> Result<()> {
+        raydium_clmm::clmm_close_position_process(ctx)
+    }
 }
@@ -0,0 +1,17 @@
+/// Program log line only compiled in with the `debug-logs` feature, production builds
+/// rely on events instead. Arguments are still type-checked without the feature.
+#[cfg(feature = "debug-logs")]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        anchor_lang::prelude::msg!($($arg)*)
+    };
+}
+
+#[cfg(not(feature = "debug-logs"))]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if false {
+            anchor_lang::prelude::msg!($($arg)*)
+        }
+    };
+}
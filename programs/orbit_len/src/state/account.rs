@@ -1,10 +1,16 @@
 use anchor_lang::prelude::*;
 
+use super::RiskTier;
+
 #[account(zero_copy(unsafe))]
 #[derive(Debug, PartialEq, Eq, InitSpace)]
 pub struct OrbitlenAccount {
     pub authority: Pubkey,
     pub lending_account: LendingAccount,
+    /// E-mode category the account opted into, `EMODE_CATEGORY_NONE` by default
+    pub emode_category: u8,
+    /// Slot the account's liquidation auction started at, 0 while its health is fine
+    pub liquidation_auction_start_slot: u64,
 }
 
 impl OrbitlenAccount {
@@ -12,6 +18,20 @@ impl OrbitlenAccount {
         self.authority = authority;
     }
 
+    /// Starts the liquidation auction at `current_slot` unless one is running already, and
+    /// returns the slots elapsed since its start
+    pub fn start_liquidation_auction(&mut self, current_slot: u64) -> u64 {
+        if self.liquidation_auction_start_slot == 0 {
+            self.liquidation_auction_start_slot = current_slot;
+        }
+
+        current_slot.saturating_sub(self.liquidation_auction_start_slot)
+    }
+
+    pub fn reset_liquidation_auction(&mut self) {
+        self.liquidation_auction_start_slot = 0;
+    }
+
     pub fn get_remaining_accounts_len(&self) -> usize {
         self.lending_account
             .balances
@@ -34,6 +54,23 @@ impl LendingAccount {
     pub fn get_first_empty_balance(&self) -> Option<usize> {
         self.balances.iter().position(|b| b.bank_pk == Pubkey::default())
     }
+
+    pub fn has_other_liabilities(&self, bank_pk: &Pubkey) -> bool {
+        self.balances
+            .iter()
+            .any(|b| b.bank_pk != Pubkey::default() && b.bank_pk != *bank_pk && b.liability_shares > 0)
+    }
+
+    /// Whether a balance in another bank holds a liability of an isolated bank, which has to
+    /// stay the only liability of the account
+    pub fn has_other_isolated_liabilities(&self, bank_pk: &Pubkey) -> bool {
+        self.balances.iter().any(|b| {
+            b.bank_pk != Pubkey::default() &&
+                b.bank_pk != *bank_pk &&
+                b.liability_shares > 0 &&
+                b.is_isolated()
+        })
+    }
 }
 
 #[zero_copy(unsafe)]
@@ -43,9 +80,15 @@ pub struct Balance {
     pub asset_shares: u64,
     pub liability_shares: u64,
     pub last_update: u64,
+    /// `RiskTier` discriminant of the bank, copied when the balance is opened
+    pub risk_tier: u8,
 }
 
 impl Balance {
+    pub fn is_isolated(&self) -> bool {
+        RiskTier::from_u8(self.risk_tier) == Some(RiskTier::Isolated)
+    }
+
     pub fn change_asset_shares(&mut self, delta: i64) -> Result<()> {
         let asset_shares = self.asset_shares as i64;
         self.asset_shares = (asset_shares + delta) as u64;
@@ -1,5 +1,5 @@
 use anchor_lang::prelude::*;
-use crate::{ state::account::*, error::*, constants::* };
+use crate::{ state::account::*, error::*, constants::*, events::*, math };
 use std::{ cmp::{ max, min }, fmt::Debug };
 use anchor_spl::token_interface::*;
 use anchor_spl::token::{ transfer_checked, TransferChecked };
@@ -24,6 +24,8 @@ pub struct Bank {
     pub last_update: i64,
 
     pub config: BankConfig,
+
+    pub interest_rate_state: InterestRateState,
 }
 
 impl Bank {
@@ -47,6 +49,7 @@ impl Bank {
             total_liability_shares: 0,
             total_asset_shares: 0,
             last_update: current_timestamp,
+            interest_rate_state: InterestRateState::new(&config.interest_rate_config),
             config,
             ..Default::default()
         }
@@ -64,7 +67,7 @@ impl Bank {
     ) -> Result<()> {
         require_keys_eq!(*to.key, self.liquidity_vault, OrbitlenError::InvalidTransfer);
 
-        msg!(
+        debug!(
             "deposit_spl_transfer: amount: {} from {} to {}, auth {}",
             amount,
             from.key,
@@ -117,7 +120,7 @@ impl Bank {
         signer_seeds: &[&[&[u8]]],
         remaining_accounts: &[AccountInfo<'info>]
     ) -> Result<()> {
-        msg!(
+        debug!(
             "withdraw_spl_transfer: amount: {} from {} to {}, auth {}",
             amount,
             from.key,
@@ -173,62 +176,117 @@ impl Bank {
     }
 
     pub fn get_liability_amount(&self, shares: u64) -> Result<u64> {
-        shares.checked_mul(self.liability_share_value.into()).ok_or(OrbitlenError::MathError.into())
+        math
+            ::shares_to_amount(shares, self.liability_share_value)
+            .ok_or(OrbitlenError::MathError.into())
     }
 
     pub fn get_asset_amount(&self, shares: u64) -> Result<u64> {
-        shares.checked_mul(self.asset_share_value).ok_or(OrbitlenError::MathError.into())
+        math
+            ::shares_to_amount(shares, self.asset_share_value)
+            .ok_or(OrbitlenError::MathError.into())
     }
 
-    pub fn accrue_interest(&mut self, current_timestamp: i64) -> Result<()> {
-        msg!("===accrue_interest===");
+    pub fn accrue_interest(&mut self, current_timestamp: i64, bank_pk: &Pubkey) -> Result<()> {
+        if let Some(event) = self.apply_interest_accrual(current_timestamp, bank_pk)? {
+            emit!(event);
+        }
+
+        Ok(())
+    }
+
+    /// `accrue_interest` without the event, which is returned instead whenever `last_update`
+    /// moved. Lets off-chain simulations step a copy of the bank.
+    pub fn apply_interest_accrual(
+        &mut self,
+        current_timestamp: i64,
+        bank_pk: &Pubkey
+    ) -> Result<Option<BankInterestAccruedEvent>> {
+        debug!("===accrue_interest===");
         let time_delta: u64 = (current_timestamp - self.last_update).try_into().unwrap();
-        msg!("time_delta: {}", time_delta);
+        debug!("time_delta: {}", time_delta);
 
         if time_delta == 0 {
-            return Ok(());
+            return Ok(None);
         }
 
         let total_assets = self.get_asset_amount(self.total_asset_shares.into())?;
         let total_liabilities = self.get_liability_amount(self.total_liability_shares.into())?;
+        let utilization_rate = math::utilization_ratio(total_assets, total_liabilities);
 
         self.last_update = current_timestamp;
 
-        msg!("total_assets: {}", total_assets);
-        msg!("total_liabilities: {}", total_liabilities);
+        debug!("total_assets: {}", total_assets);
+        debug!("total_liabilities: {}", total_liabilities);
+
+        // Rates for the elapsed period are priced with the state that was in effect
+        // during it, the controller only moves afterwards.
+        let interest_rate_state = self.interest_rate_state;
+        if total_assets > 0 {
+            self.interest_rate_state.update(
+                &self.config.interest_rate_config,
+                utilization_rate,
+                time_delta
+            );
+        }
+
+        let (old_asset_share_value, old_liability_share_value) = (
+            self.asset_share_value,
+            self.liability_share_value,
+        );
+
+        // Nothing accrues without both lenders and borrowers, the event still reports the
+        // rates in effect
         if total_assets == 0 || total_liabilities == 0 {
-            return Ok(());
-        }
-
-        let debug_self_asset_share_value = self.asset_share_value;
-        let debug_self_liability_share_value = self.liability_share_value;
-
-        msg!("asset_share_value: {}", debug_self_asset_share_value);
-        msg!("liability_share_value: {}", debug_self_liability_share_value);
-        msg!("interest_rate_config: {:?}", self.config.interest_rate_config);
-
-        let (asset_share_value, liability_share_value) = calc_interest_rate_accrual_state_changes(
-            time_delta,
-            total_assets,
-            total_liabilities,
-            &self.config.interest_rate_config,
-            self.asset_share_value.into(),
-            self.liability_share_value.into()
-        ).ok_or_else(|| {
-            let debug_self_asset_share_value = self.asset_share_value;
-            let debug_self_liability_share_value = self.liability_share_value;
-            msg!(
-                "Failed to calculate interest: time_delta={}, total_assets={}, total_liabilities={}, asset_share_value={}, liability_share_value={}",
+            let (lending_apr, borrowing_apr) = self.config.interest_rate_config
+                .calc_interest_rate(utilization_rate, &interest_rate_state)
+                .ok_or(OrbitlenError::MathError)?;
+
+            return Ok(
+                Some(BankInterestAccruedEvent {
+                    bank: *bank_pk,
+                    mint: self.mint,
+                    time_delta,
+                    total_assets,
+                    total_liabilities,
+                    utilization_rate: utilization_rate as f64,
+                    old_asset_share_value,
+                    old_liability_share_value,
+                    new_asset_share_value: old_asset_share_value,
+                    new_liability_share_value: old_liability_share_value,
+                    lending_apr: lending_apr as f64,
+                    borrowing_apr: borrowing_apr as f64,
+                    fees: 0,
+                })
+            );
+        }
+
+        debug!("asset_share_value: {}", old_asset_share_value);
+        debug!("liability_share_value: {}", old_liability_share_value);
+        debug!("interest_rate_config: {:?}", self.config.interest_rate_config);
+
+        let (asset_share_value, liability_share_value, lending_apr, borrowing_apr) =
+            calc_interest_rate_accrual_state_changes(
                 time_delta,
                 total_assets,
                 total_liabilities,
-                debug_self_asset_share_value,
-                debug_self_liability_share_value
-            );
-            OrbitlenError::MathError
-        })?;
-
-        msg!(
+                &self.config.interest_rate_config,
+                &interest_rate_state,
+                old_asset_share_value,
+                old_liability_share_value
+            ).ok_or_else(|| {
+                debug!(
+                    "Failed to calculate interest: time_delta={}, total_assets={}, total_liabilities={}, asset_share_value={}, liability_share_value={}",
+                    time_delta,
+                    total_assets,
+                    total_liabilities,
+                    old_asset_share_value,
+                    old_liability_share_value
+                );
+                OrbitlenError::MathError
+            })?;
+
+        debug!(
             "deposit share value: {}\nliability share value: {}",
             asset_share_value,
             liability_share_value
@@ -237,11 +295,75 @@ impl Bank {
         self.asset_share_value = asset_share_value;
         self.liability_share_value = liability_share_value;
 
-        Ok(())
+        // Whatever borrowers pay on top of what lenders earn stays in the vault
+        let lending_interest = self.total_asset_shares
+            .checked_mul(asset_share_value.saturating_sub(old_asset_share_value))
+            .ok_or(OrbitlenError::MathError)?;
+        let borrowing_interest = self.total_liability_shares
+            .checked_mul(liability_share_value.saturating_sub(old_liability_share_value))
+            .ok_or(OrbitlenError::MathError)?;
+
+        Ok(
+            Some(BankInterestAccruedEvent {
+                bank: *bank_pk,
+                mint: self.mint,
+                time_delta,
+                total_assets,
+                total_liabilities,
+                utilization_rate: utilization_rate as f64,
+                old_asset_share_value,
+                old_liability_share_value,
+                new_asset_share_value: asset_share_value,
+                new_liability_share_value: liability_share_value,
+                lending_apr: lending_apr as f64,
+                borrowing_apr: borrowing_apr as f64,
+                fees: borrowing_interest.saturating_sub(lending_interest),
+            })
+        )
+    }
+
+    pub fn get_utilization_rate(&self) -> Result<f32> {
+        let total_assets = self.get_asset_amount(self.total_asset_shares.into())?;
+        let total_liabilities = self.get_liability_amount(self.total_liability_shares.into())?;
+
+        Ok(math::utilization_ratio(total_assets, total_liabilities))
+    }
+
+    /// Returns `(lending_apr, borrowing_apr)` at the current utilization.
+    pub fn get_interest_rates(&self) -> Result<(f32, f32)> {
+        let interest_rate_state = self.interest_rate_state;
+        self.config.interest_rate_config
+            .calc_interest_rate(self.get_utilization_rate()?, &interest_rate_state)
+            .ok_or_else(|| error!(OrbitlenError::MathError))
+    }
+
+    /// `BankInterestAccruedEvent` of an accrual over no time, reporting the current share
+    /// values, utilization and rates. For the crank, which reports the bank even when it was
+    /// already accrued this second.
+    pub fn get_current_interest_event(&self, bank_pk: &Pubkey) -> Result<BankInterestAccruedEvent> {
+        let (lending_apr, borrowing_apr) = self.get_interest_rates()?;
+
+        Ok(BankInterestAccruedEvent {
+            bank: *bank_pk,
+            mint: self.mint,
+            time_delta: 0,
+            total_assets: self.get_asset_amount(self.total_asset_shares)?,
+            total_liabilities: self.get_liability_amount(self.total_liability_shares)?,
+            utilization_rate: self.get_utilization_rate()? as f64,
+            old_asset_share_value: self.asset_share_value,
+            old_liability_share_value: self.liability_share_value,
+            new_asset_share_value: self.asset_share_value,
+            new_liability_share_value: self.liability_share_value,
+            lending_apr: lending_apr as f64,
+            borrowing_apr: borrowing_apr as f64,
+            fees: 0,
+        })
     }
 
     pub fn get_asset_shares(&self, value: u64) -> Result<u64> {
-        Ok(value.checked_div(self.asset_share_value.into()).ok_or(OrbitlenError::MathError)?)
+        math
+            ::amount_to_shares(value, self.asset_share_value)
+            .ok_or(OrbitlenError::MathError.into())
     }
 
     pub fn change_asset_shares(&mut self, shares: i64) -> Result<()> {
@@ -251,7 +373,9 @@ impl Bank {
     }
 
     pub fn get_liability_shares(&self, value: u64) -> Result<u64> {
-        Ok(value.checked_div(self.liability_share_value.into()).ok_or(OrbitlenError::MathError)?)
+        math
+            ::amount_to_shares(value, self.liability_share_value)
+            .ok_or(OrbitlenError::MathError.into())
     }
 
     pub fn change_liability_shares(&mut self, shares: i64) -> Result<()> {
@@ -266,48 +390,283 @@ fn calc_interest_rate_accrual_state_changes(
     total_assets_amount: u64,
     total_liabilities_amount: u64,
     interest_rate_config: &InterestRateConfig,
+    interest_rate_state: &InterestRateState,
     asset_share_value: u64,
     liability_share_value: u64
-) -> Option<(u64, u64)> {
-    msg!("=== calc_interest_rate_accrual_state_changes ===");
-    let utilization_rate = (total_liabilities_amount as f64) / (total_assets_amount as f64);
-    msg!("utilization_rate: {}", utilization_rate);
+) -> Option<(u64, u64, f32, f32)> {
+    debug!("=== calc_interest_rate_accrual_state_changes ===");
+    let utilization_rate = math::accrual_utilization_ratio(
+        total_assets_amount,
+        total_liabilities_amount
+    );
+    debug!("utilization_rate: {}", utilization_rate);
 
     let (lending_apr, borrowing_apr) = interest_rate_config.calc_interest_rate(
-        utilization_rate as f32
+        utilization_rate,
+        interest_rate_state
     )?;
-    msg!("lending_apr: {}, borrowing_apr: {}", lending_apr, borrowing_apr);
+    debug!("lending_apr: {}, borrowing_apr: {}", lending_apr, borrowing_apr);
     Some((
-        calc_accrued_interest_payment_per_period(
-            lending_apr,
-            time_delta as f32,
-            asset_share_value as f32
-        )?,
-        calc_accrued_interest_payment_per_period(
-            borrowing_apr,
-            time_delta as f32,
-            liability_share_value as f32
-        )?,
+        math::accrue_share_value(asset_share_value, lending_apr, time_delta),
+        math::accrue_share_value(liability_share_value, borrowing_apr, time_delta),
+        lending_apr,
+        borrowing_apr,
     ))
 }
 
-fn calc_accrued_interest_payment_per_period(apr: f32, time_delta: f32, value: f32) -> Option<u64> {
-    msg!("APR: {}, time_delta: {}, value: {}", apr, time_delta, value);
-    let ir_per_period = (apr * time_delta) / (SECONDS_PER_YEAR as f32);
-    msg!("Interest rate per period: {}", ir_per_period);
-    let new_value = value * (1.0 + ir_per_period);
-    msg!("New value: {}", new_value);
-    Some(new_value as u64)
-}
-
 #[zero_copy(unsafe)]
 #[derive(PartialEq, Debug, InitSpace, Default)]
 pub struct BankConfig {
     pub interest_rate_config: InterestRateConfig,
     pub feed_data_key: Pubkey,
+
+    /// `BankType` discriminant
+    pub bank_type: u8,
+    pub raydium_lp_config: RaydiumLpConfig,
+
+    // Risk weights in basis points, the initial ones gate new risk and the maintenance ones
+    // liquidation
+    pub asset_weight_init_bps: u16,
+    pub asset_weight_maint_bps: u16,
+    pub liability_weight_init_bps: u16,
+    pub liability_weight_maint_bps: u16,
+
+    /// `RiskTier` discriminant
+    pub risk_tier: u8,
+
+    /// E-mode category the bank is listed in, `EMODE_CATEGORY_NONE` when in none. The weights
+    /// of a category live in its `EmodeCategory` account.
+    pub emode_category: u8,
+
+    /// Share of a liability in this bank one liquidation may repay, in basis points
+    pub close_factor_bps: u16,
+
+    /// `LiquidationMode` discriminant, how the bonus on collateral seized from this bank is set
+    pub liquidation_mode: u8,
+    /// Bonus liquidators receive on seized collateral, in basis points of the repaid value
+    pub liquidation_max_bonus_bps: u16,
+    /// Slots an auction takes to raise the bonus from zero to `liquidation_max_bonus_bps`
+    pub liquidation_auction_slots: u32,
+
+    pub raydium_cpmm_lp_config: RaydiumCpmmLpConfig,
 }
 
-impl BankConfig {}
+impl BankConfig {
+    pub fn get_bank_type(&self) -> Option<BankType> {
+        BankType::from_u8(self.bank_type)
+    }
+
+    pub fn get_risk_tier(&self) -> Option<RiskTier> {
+        RiskTier::from_u8(self.risk_tier)
+    }
+
+    pub fn is_isolated(&self) -> bool {
+        self.get_risk_tier() == Some(RiskTier::Isolated)
+    }
+
+    pub fn get_liquidation_mode(&self) -> Option<LiquidationMode> {
+        LiquidationMode::from_u8(self.liquidation_mode)
+    }
+
+    /// Bonus on collateral seized from this bank `elapsed_slots` into the liquidatee's auction
+    pub fn get_liquidation_bonus_bps(&self, elapsed_slots: u64) -> u32 {
+        let max_bonus_bps = self.liquidation_max_bonus_bps as u32;
+
+        match self.get_liquidation_mode() {
+            Some(LiquidationMode::Auction) => {
+                math::auction_liquidation_bonus_bps(
+                    max_bonus_bps,
+                    self.liquidation_auction_slots as u64,
+                    elapsed_slots
+                )
+            }
+            _ => max_bonus_bps,
+        }
+    }
+
+    /// LP positions are collateral only, they can not be lent out
+    pub fn is_borrowable(&self) -> bool {
+        self.get_bank_type() == Some(BankType::Standard)
+    }
+
+    /// Number of oracle accounts `fetch_bank_price` expects for this bank
+    pub fn get_oracle_ais_len(&self) -> usize {
+        match self.get_bank_type() {
+            Some(BankType::RaydiumLp) => RAYDIUM_LP_ORACLE_AIS_LEN,
+            Some(BankType::RaydiumCpmmLp) => RAYDIUM_CPMM_LP_ORACLE_AIS_LEN,
+            _ => 1,
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        self.interest_rate_config.validate()?;
+
+        require!(self.get_risk_tier().is_some(), OrbitlenError::InvalidBankConfig);
+        require!(
+            self.close_factor_bps > 0 && (self.close_factor_bps as u32) <= BASIS_POINTS,
+            OrbitlenError::InvalidBankConfig
+        );
+
+        require!(
+            (self.liquidation_max_bonus_bps as u32) <= BASIS_POINTS,
+            OrbitlenError::InvalidBankConfig
+        );
+        match self.get_liquidation_mode() {
+            Some(LiquidationMode::Fixed) => {}
+            Some(LiquidationMode::Auction) => {
+                require_gt!(self.liquidation_auction_slots, 0, OrbitlenError::InvalidBankConfig);
+            }
+            None => {
+                return err!(OrbitlenError::InvalidBankConfig);
+            }
+        }
+
+        // Collateral is discounted and debt marked up, more so for the initial requirement
+        require!(
+            self.asset_weight_init_bps <= self.asset_weight_maint_bps &&
+                (self.asset_weight_maint_bps as u32) <= BASIS_POINTS,
+            OrbitlenError::InvalidBankConfig
+        );
+        require!(
+            self.liability_weight_init_bps >= self.liability_weight_maint_bps &&
+                (self.liability_weight_maint_bps as u32) >= BASIS_POINTS,
+            OrbitlenError::InvalidBankConfig
+        );
+
+        match self.get_bank_type() {
+            Some(BankType::Standard) => {}
+            Some(BankType::RaydiumLp) => {
+                self.raydium_lp_config.validate()?;
+            }
+            Some(BankType::RaydiumCpmmLp) => {
+                self.raydium_cpmm_lp_config.validate()?;
+            }
+            None => {
+                return err!(OrbitlenError::InvalidBankConfig);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, AnchorDeserialize, AnchorSerialize)]
+pub enum BankType {
+    /// Plain token bank priced by `feed_data_key`
+    Standard,
+    /// Bank holding Raydium AMM LP tokens, priced from the pool invariant and both legs' feeds
+    RaydiumLp,
+    /// Bank holding Raydium CPMM LP tokens, priced from the pool invariant net of the fees
+    /// owed to Raydium and both tokens' feeds
+    RaydiumCpmmLp,
+}
+
+impl BankType {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(BankType::Standard),
+            1 => Some(BankType::RaydiumLp),
+            2 => Some(BankType::RaydiumCpmmLp),
+            _ => None,
+        }
+    }
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, AnchorDeserialize, AnchorSerialize)]
+pub enum RiskTier {
+    /// Cross collateral, can be borrowed next to any other liability
+    Collateral,
+    /// Long-tail mint, an account borrowing it can't hold any other liability
+    Isolated,
+}
+
+impl RiskTier {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(RiskTier::Collateral),
+            1 => Some(RiskTier::Isolated),
+            _ => None,
+        }
+    }
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, AnchorDeserialize, AnchorSerialize)]
+pub enum LiquidationMode {
+    /// Liquidators always receive the full bonus
+    Fixed,
+    /// Dutch auction, the bonus starts at zero once the account is liquidatable and rises
+    /// linearly over `liquidation_auction_slots`
+    Auction,
+}
+
+impl LiquidationMode {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(LiquidationMode::Fixed),
+            1 => Some(LiquidationMode::Auction),
+            _ => None,
+        }
+    }
+}
+
+#[zero_copy(unsafe)]
+#[derive(PartialEq, Debug, InitSpace, Default)]
+pub struct RaydiumLpConfig {
+    pub amm: Pubkey,
+    pub amm_open_orders: Pubkey,
+    pub amm_coin_vault: Pubkey,
+    pub amm_pc_vault: Pubkey,
+    pub coin_mint_decimals: u8,
+    pub pc_mint_decimals: u8,
+    pub coin_feed_data_key: Pubkey,
+    pub pc_feed_data_key: Pubkey,
+}
+
+impl RaydiumLpConfig {
+    pub fn validate(&self) -> Result<()> {
+        for key in [
+            self.amm,
+            self.amm_open_orders,
+            self.amm_coin_vault,
+            self.amm_pc_vault,
+            self.coin_feed_data_key,
+            self.pc_feed_data_key,
+        ] {
+            require_keys_neq!(key, Pubkey::default(), OrbitlenError::InvalidBankConfig);
+        }
+
+        Ok(())
+    }
+}
+
+#[zero_copy(unsafe)]
+#[derive(PartialEq, Debug, InitSpace, Default)]
+pub struct RaydiumCpmmLpConfig {
+    pub pool_state: Pubkey,
+    pub token_0_vault: Pubkey,
+    pub token_1_vault: Pubkey,
+    pub token_0_feed_data_key: Pubkey,
+    pub token_1_feed_data_key: Pubkey,
+}
+
+impl RaydiumCpmmLpConfig {
+    pub fn validate(&self) -> Result<()> {
+        for key in [
+            self.pool_state,
+            self.token_0_vault,
+            self.token_1_vault,
+            self.token_0_feed_data_key,
+            self.token_1_feed_data_key,
+        ] {
+            require_keys_neq!(key, Pubkey::default(), OrbitlenError::InvalidBankConfig);
+        }
+
+        Ok(())
+    }
+}
 
 #[zero_copy(unsafe)]
 #[derive(PartialEq, Default, Debug, InitSpace)]
@@ -315,29 +674,265 @@ pub struct InterestRateConfig {
     pub optimal_utilization_rate: u16,
     pub plateau_interest_rate: u16,
     pub max_interest_rate: u16,
+
+    /// `InterestRateCurveType` discriminant
+    pub curve_type: u8,
+
+    // Multi-kink curve, rates and utilizations in basis points
+    pub base_interest_rate_bps: u32,
+    pub kink_count: u8,
+    pub kinks: [InterestRateKink; MAX_INTEREST_RATE_KINKS],
+    pub max_interest_rate_bps: u32,
+
+    pub adaptive_config: AdaptiveRateConfig,
 }
 
 impl InterestRateConfig {
     pub fn as_float(&self, value: u16) -> f32 {
-        (value as f32) / 100.0
+        math::hundredths_as_float(value)
+    }
+
+    pub fn bps_as_float(value: u32) -> f32 {
+        math::bps_as_float(value)
+    }
+
+    pub fn get_curve_type(&self) -> Option<InterestRateCurveType> {
+        InterestRateCurveType::from_u8(self.curve_type)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        match self.get_curve_type() {
+            Some(InterestRateCurveType::Linear) => {
+                require!(
+                    self.optimal_utilization_rate > 0 && self.optimal_utilization_rate < 100,
+                    OrbitlenError::InvalidInterestRateConfig
+                );
+            }
+            Some(InterestRateCurveType::Adaptive) => {
+                require!(
+                    self.optimal_utilization_rate > 0 && self.optimal_utilization_rate < 100,
+                    OrbitlenError::InvalidInterestRateConfig
+                );
+                self.adaptive_config.validate()?;
+            }
+            Some(InterestRateCurveType::MultiKink) => {
+                require!(
+                    (self.kink_count as usize) <= MAX_INTEREST_RATE_KINKS,
+                    OrbitlenError::InvalidInterestRateConfig
+                );
+
+                // Kinks must be strictly increasing and lie inside (0%, 100%), and the rate may
+                // never drop as utilization rises
+                let mut prev_utilization_rate = 0;
+                let mut prev_interest_rate = self.base_interest_rate_bps;
+                for kink in self.kinks[..self.kink_count as usize].iter() {
+                    let utilization_rate = kink.utilization_rate_bps as u32;
+                    require!(
+                        utilization_rate > prev_utilization_rate && utilization_rate < BASIS_POINTS,
+                        OrbitlenError::InvalidInterestRateConfig
+                    );
+                    require_gte!(
+                        kink.interest_rate_bps,
+                        prev_interest_rate,
+                        OrbitlenError::InvalidInterestRateConfig
+                    );
+                    prev_utilization_rate = utilization_rate;
+                    prev_interest_rate = kink.interest_rate_bps;
+                }
+
+                require_gte!(
+                    self.max_interest_rate_bps,
+                    prev_interest_rate,
+                    OrbitlenError::InvalidInterestRateConfig
+                );
+            }
+            None => {
+                return err!(OrbitlenError::InvalidInterestRateConfig);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn calc_interest_rate(
+        &self,
+        utilization_ratio: f32,
+        interest_rate_state: &InterestRateState
+    ) -> Option<(f32, f32)> {
+        debug!("=== Interest Rate Calculation ===");
+        debug!("utilization_ratio: {}", utilization_ratio);
+        let base_rate = self.interest_rate_curve(utilization_ratio, interest_rate_state)?;
+        Some(math::interest_rates(base_rate, utilization_ratio))
+    }
+
+    fn interest_rate_curve(&self, ur: f32, interest_rate_state: &InterestRateState) -> Option<f32> {
+        match self.get_curve_type()? {
+            InterestRateCurveType::Linear => self.linear_interest_rate_curve(ur),
+            InterestRateCurveType::MultiKink => self.multi_kink_interest_rate_curve(ur),
+            InterestRateCurveType::Adaptive =>
+                self.adaptive_interest_rate_curve(ur, interest_rate_state.rate_at_target),
+        }
     }
-    pub fn calc_interest_rate(&self, utilization_ratio: f32) -> Option<(f32, f32)> {
-        msg!("=== Interest Rate Calculation ===");
-        msg!("utilization_ratio: {}", utilization_ratio);
-        let base_rate = self.interest_rate_curve(utilization_ratio)?;
-        let lending_rate = base_rate * utilization_ratio;
-        let borrowing_rate = base_rate;
-        Some((lending_rate, borrowing_rate))
+
+    fn linear_interest_rate_curve(&self, ur: f32) -> Option<f32> {
+        Some(
+            math::linear_interest_rate(
+                ur,
+                self.as_float(self.optimal_utilization_rate),
+                self.as_float(self.plateau_interest_rate),
+                self.as_float(self.max_interest_rate)
+            )
+        )
+    }
+
+    fn multi_kink_interest_rate_curve(&self, ur: f32) -> Option<f32> {
+        let kinks = self.kinks.get(..self.kink_count as usize)?;
+
+        Some(
+            math::multi_kink_interest_rate(
+                ur,
+                Self::bps_as_float(self.base_interest_rate_bps),
+                kinks.iter().map(|kink| {
+                    (
+                        Self::bps_as_float(kink.utilization_rate_bps as u32),
+                        Self::bps_as_float(kink.interest_rate_bps),
+                    )
+                }),
+                Self::bps_as_float(self.max_interest_rate_bps)
+            )
+        )
+    }
+
+    fn adaptive_interest_rate_curve(&self, ur: f32, rate_at_target: f32) -> Option<f32> {
+        Some(
+            math::adaptive_interest_rate(
+                ur,
+                self.as_float(self.optimal_utilization_rate),
+                rate_at_target
+            )
+        )
+    }
+}
+
+#[zero_copy(unsafe)]
+#[derive(PartialEq, Default, Debug, InitSpace)]
+pub struct AdaptiveRateConfig {
+    pub initial_rate_at_target_bps: u32,
+    pub min_rate_at_target_bps: u32,
+    pub max_rate_at_target_bps: u32,
+    /// Proportional gain, APR change per unit change of the normalized utilization error
+    pub kp: f32,
+    /// Integral gain, APR change per year spent at a normalized utilization error of 1
+    pub ki: f32,
+}
+
+impl AdaptiveRateConfig {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.min_rate_at_target_bps <= self.initial_rate_at_target_bps &&
+                self.initial_rate_at_target_bps <= self.max_rate_at_target_bps &&
+                self.max_rate_at_target_bps > 0,
+            OrbitlenError::InvalidInterestRateConfig
+        );
+
+        let (kp, ki) = (self.kp, self.ki);
+        require!(
+            kp.is_finite() && kp >= 0.0 && ki.is_finite() && ki >= 0.0,
+            OrbitlenError::InvalidInterestRateConfig
+        );
+
+        Ok(())
     }
+}
 
-    fn interest_rate_curve(&self, ur: f32) -> Option<f32> {
-        let optimal_ur = self.as_float(self.optimal_utilization_rate);
-        let plateau_ir = self.as_float(self.plateau_interest_rate);
-        let max_ir = self.as_float(self.max_interest_rate);
-        if ur <= optimal_ur {
-            Some((ur / optimal_ur) * plateau_ir)
-        } else {
-            Some(((ur - optimal_ur) / (1.0 - optimal_ur)) * (max_ir - plateau_ir) + plateau_ir)
+/// Controller state of the adaptive interest rate curve, unused by static curves.
+#[zero_copy(unsafe)]
+#[derive(PartialEq, Default, Debug, InitSpace)]
+pub struct InterestRateState {
+    /// Borrowing APR at `optimal_utilization_rate`
+    pub rate_at_target: f32,
+    /// Normalized utilization error observed at the last update
+    pub last_error: f32,
+}
+
+impl InterestRateState {
+    pub fn new(interest_rate_config: &InterestRateConfig) -> Self {
+        Self {
+            rate_at_target: InterestRateConfig::bps_as_float(
+                interest_rate_config.adaptive_config.initial_rate_at_target_bps
+            ),
+            last_error: 0.0,
+        }
+    }
+
+    /// Velocity-form PI step: the rate at target moves by the change in error times `kp`
+    /// plus the error integrated over `time_delta` times `ki`, clamped to the configured bounds.
+    /// There is no derivative term, utilization only gets sampled when the bank is touched.
+    pub fn update(
+        &mut self,
+        interest_rate_config: &InterestRateConfig,
+        utilization_ratio: f32,
+        time_delta: u64
+    ) {
+        if interest_rate_config.get_curve_type() != Some(InterestRateCurveType::Adaptive) {
+            return;
+        }
+
+        let adaptive_config = interest_rate_config.adaptive_config;
+        let optimal_ur = interest_rate_config.as_float(
+            interest_rate_config.optimal_utilization_rate
+        );
+
+        let error = math::utilization_error(utilization_ratio, optimal_ur);
+
+        let new_rate_at_target = math::adaptive_rate_at_target(
+            self.rate_at_target,
+            self.last_error,
+            error,
+            adaptive_config.kp,
+            adaptive_config.ki,
+            time_delta
+        );
+
+        self.rate_at_target = new_rate_at_target.clamp(
+            InterestRateConfig::bps_as_float(adaptive_config.min_rate_at_target_bps),
+            InterestRateConfig::bps_as_float(adaptive_config.max_rate_at_target_bps)
+        );
+        self.last_error = error;
+
+        let debug_rate_at_target = self.rate_at_target;
+        debug!("rate_at_target: {}, error: {}", debug_rate_at_target, error);
+    }
+}
+
+#[zero_copy(unsafe)]
+#[derive(PartialEq, Default, Debug, InitSpace)]
+pub struct InterestRateKink {
+    pub utilization_rate_bps: u16,
+    pub interest_rate_bps: u32,
+}
+
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, AnchorDeserialize, AnchorSerialize)]
+pub enum InterestRateCurveType {
+    /// Single kink curve defined by `optimal_utilization_rate`, `plateau_interest_rate`
+    /// and `max_interest_rate` in hundredths
+    Linear,
+    /// Base rate at 0% utilization, up to `MAX_INTEREST_RATE_KINKS` kinks and a max rate
+    /// at 100% utilization, all in basis points
+    MultiKink,
+    /// Curve whose rate at `optimal_utilization_rate` is steered by a controller, see
+    /// `InterestRateState`
+    Adaptive,
+}
+
+impl InterestRateCurveType {
+    pub fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(InterestRateCurveType::Linear),
+            1 => Some(InterestRateCurveType::MultiKink),
+            2 => Some(InterestRateCurveType::Adaptive),
+            _ => None,
         }
     }
 }
@@ -346,6 +941,19 @@ impl InterestRateConfig {
 pub struct BankConfigCompact {
     pub interest_rate_config: InterestRateConfigCompact,
     pub feed_data_key: Pubkey,
+    pub bank_type: BankType,
+    pub raydium_lp_config: Option<RaydiumLpConfigCompact>,
+    pub asset_weight_init_bps: u16,
+    pub asset_weight_maint_bps: u16,
+    pub liability_weight_init_bps: u16,
+    pub liability_weight_maint_bps: u16,
+    pub risk_tier: RiskTier,
+    pub emode_category: u8,
+    pub close_factor_bps: u16,
+    pub liquidation_mode: LiquidationMode,
+    pub liquidation_max_bonus_bps: u16,
+    pub liquidation_auction_slots: u32,
+    pub raydium_cpmm_lp_config: Option<RaydiumCpmmLpConfigCompact>,
 }
 
 impl From<BankConfigCompact> for BankConfig {
@@ -353,6 +961,69 @@ impl From<BankConfigCompact> for BankConfig {
         Self {
             interest_rate_config: config.interest_rate_config.into(),
             feed_data_key: config.feed_data_key,
+            bank_type: config.bank_type as u8,
+            raydium_lp_config: config.raydium_lp_config.map(Into::into).unwrap_or_default(),
+            asset_weight_init_bps: config.asset_weight_init_bps,
+            asset_weight_maint_bps: config.asset_weight_maint_bps,
+            liability_weight_init_bps: config.liability_weight_init_bps,
+            liability_weight_maint_bps: config.liability_weight_maint_bps,
+            risk_tier: config.risk_tier as u8,
+            emode_category: config.emode_category,
+            close_factor_bps: config.close_factor_bps,
+            liquidation_mode: config.liquidation_mode as u8,
+            liquidation_max_bonus_bps: config.liquidation_max_bonus_bps,
+            liquidation_auction_slots: config.liquidation_auction_slots,
+            raydium_cpmm_lp_config: config.raydium_cpmm_lp_config
+                .map(Into::into)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(PartialEq, AnchorDeserialize, AnchorSerialize, Debug)]
+pub struct RaydiumLpConfigCompact {
+    pub amm: Pubkey,
+    pub amm_open_orders: Pubkey,
+    pub amm_coin_vault: Pubkey,
+    pub amm_pc_vault: Pubkey,
+    pub coin_mint_decimals: u8,
+    pub pc_mint_decimals: u8,
+    pub coin_feed_data_key: Pubkey,
+    pub pc_feed_data_key: Pubkey,
+}
+
+impl From<RaydiumLpConfigCompact> for RaydiumLpConfig {
+    fn from(config: RaydiumLpConfigCompact) -> Self {
+        Self {
+            amm: config.amm,
+            amm_open_orders: config.amm_open_orders,
+            amm_coin_vault: config.amm_coin_vault,
+            amm_pc_vault: config.amm_pc_vault,
+            coin_mint_decimals: config.coin_mint_decimals,
+            pc_mint_decimals: config.pc_mint_decimals,
+            coin_feed_data_key: config.coin_feed_data_key,
+            pc_feed_data_key: config.pc_feed_data_key,
+        }
+    }
+}
+
+#[derive(PartialEq, AnchorDeserialize, AnchorSerialize, Debug)]
+pub struct RaydiumCpmmLpConfigCompact {
+    pub pool_state: Pubkey,
+    pub token_0_vault: Pubkey,
+    pub token_1_vault: Pubkey,
+    pub token_0_feed_data_key: Pubkey,
+    pub token_1_feed_data_key: Pubkey,
+}
+
+impl From<RaydiumCpmmLpConfigCompact> for RaydiumCpmmLpConfig {
+    fn from(config: RaydiumCpmmLpConfigCompact) -> Self {
+        Self {
+            pool_state: config.pool_state,
+            token_0_vault: config.token_0_vault,
+            token_1_vault: config.token_1_vault,
+            token_0_feed_data_key: config.token_0_feed_data_key,
+            token_1_feed_data_key: config.token_1_feed_data_key,
         }
     }
 }
@@ -362,14 +1033,61 @@ pub struct InterestRateConfigCompact {
     pub optimal_utilization_rate: u16,
     pub plateau_interest_rate: u16,
     pub max_interest_rate: u16,
+    pub curve_type: InterestRateCurveType,
+    pub base_interest_rate_bps: u32,
+    pub kinks: Vec<InterestRateKinkCompact>,
+    pub max_interest_rate_bps: u32,
+    pub adaptive_config: AdaptiveRateConfigCompact,
 }
 
 impl From<InterestRateConfigCompact> for InterestRateConfig {
     fn from(ir_config: InterestRateConfigCompact) -> Self {
+        let mut kinks = [InterestRateKink::default(); MAX_INTEREST_RATE_KINKS];
+        for (kink, compact) in kinks.iter_mut().zip(ir_config.kinks.iter()) {
+            *kink = InterestRateKink {
+                utilization_rate_bps: compact.utilization_rate_bps,
+                interest_rate_bps: compact.interest_rate_bps,
+            };
+        }
+
         InterestRateConfig {
             optimal_utilization_rate: ir_config.optimal_utilization_rate,
             plateau_interest_rate: ir_config.plateau_interest_rate,
             max_interest_rate: ir_config.max_interest_rate,
+            curve_type: ir_config.curve_type as u8,
+            base_interest_rate_bps: ir_config.base_interest_rate_bps,
+            // Oversized kink lists are kept as-is so `validate` can reject them
+            kink_count: ir_config.kinks.len().min(u8::MAX as usize) as u8,
+            kinks,
+            max_interest_rate_bps: ir_config.max_interest_rate_bps,
+            adaptive_config: ir_config.adaptive_config.into(),
+        }
+    }
+}
+
+#[derive(Debug, AnchorDeserialize, AnchorSerialize, PartialEq)]
+pub struct InterestRateKinkCompact {
+    pub utilization_rate_bps: u16,
+    pub interest_rate_bps: u32,
+}
+
+#[derive(Debug, AnchorDeserialize, AnchorSerialize, PartialEq)]
+pub struct AdaptiveRateConfigCompact {
+    pub initial_rate_at_target_bps: u32,
+    pub min_rate_at_target_bps: u32,
+    pub max_rate_at_target_bps: u32,
+    pub kp: f32,
+    pub ki: f32,
+}
+
+impl From<AdaptiveRateConfigCompact> for AdaptiveRateConfig {
+    fn from(config: AdaptiveRateConfigCompact) -> Self {
+        AdaptiveRateConfig {
+            initial_rate_at_target_bps: config.initial_rate_at_target_bps,
+            min_rate_at_target_bps: config.min_rate_at_target_bps,
+            max_rate_at_target_bps: config.max_rate_at_target_bps,
+            kp: config.kp,
+            ki: config.ki,
         }
     }
 }
@@ -377,6 +1095,12 @@ impl From<InterestRateConfigCompact> for InterestRateConfig {
 pub struct BankAccountWrapper<'a> {
     pub balance: &'a mut Balance,
     pub bank: &'a mut Bank,
+    /// Whether another balance of the account holds a liability, an isolated bank can't be
+    /// borrowed from then
+    has_other_liabilities: bool,
+    /// Whether another balance holds an isolated liability, no other bank can be borrowed
+    /// from then
+    has_other_isolated_liabilities: bool,
 }
 
 impl<'a> BankAccountWrapper<'a> {
@@ -385,18 +1109,24 @@ impl<'a> BankAccountWrapper<'a> {
         bank: &'a mut Bank,
         lending_account: &'a mut LendingAccount
     ) -> Result<BankAccountWrapper<'a>> {
+        let has_other_liabilities = lending_account.has_other_liabilities(bank_pk);
+        let has_other_isolated_liabilities = lending_account.has_other_isolated_liabilities(bank_pk);
+
         let balance = lending_account.balances
             .iter_mut()
             .find(|balance| balance.bank_pk.eq(bank_pk))
             .ok_or_else(|| error!(OrbitlenError::BankAccountNotFound))?;
 
-        Ok(Self { balance, bank })
+        Ok(Self { balance, bank, has_other_liabilities, has_other_isolated_liabilities })
     }
     pub fn find_or_create(
         bank_pk: &Pubkey,
         bank: &'a mut Bank,
         lending_account: &'a mut LendingAccount
     ) -> Result<BankAccountWrapper<'a>> {
+        let has_other_liabilities = lending_account.has_other_liabilities(bank_pk);
+        let has_other_isolated_liabilities = lending_account.has_other_isolated_liabilities(bank_pk);
+
         let balance_index = lending_account.balances
             .iter()
             .position(|balance| balance.bank_pk.eq(bank_pk));
@@ -407,7 +1137,7 @@ impl<'a> BankAccountWrapper<'a> {
                     .get_mut(balance_index)
                     .ok_or_else(|| error!(OrbitlenError::BankAccountNotFound))?;
 
-                Ok(Self { balance, bank })
+                Ok(Self { balance, bank, has_other_liabilities, has_other_isolated_liabilities })
             }
             None => {
                 let empty_index = lending_account
@@ -419,11 +1149,14 @@ impl<'a> BankAccountWrapper<'a> {
                     asset_shares: 0,
                     liability_shares: 0,
                     last_update: Clock::get()?.unix_timestamp as u64,
+                    risk_tier: bank.config.risk_tier,
                 };
 
                 Ok(Self {
                     balance: lending_account.balances.get_mut(empty_index).unwrap(),
                     bank,
+                    has_other_liabilities,
+                    has_other_isolated_liabilities,
                 })
             }
         }
@@ -438,7 +1171,7 @@ impl<'a> BankAccountWrapper<'a> {
     }
 
     fn increase_balance_internal(&mut self, balance_delta: i64) -> Result<()> {
-        msg!("Balance increase: {} ", balance_delta);
+        debug!("Balance increase: {} ", balance_delta);
 
         let balance = &mut self.balance;
         let bank = &mut self.bank;
@@ -490,15 +1223,17 @@ impl<'a> BankAccountWrapper<'a> {
     }
 
     fn decrease_balance_internal(&mut self, balance_delta: i64) -> Result<()> {
-        msg!("Balance decrease: {}", balance_delta);
+        debug!("Balance decrease: {}", balance_delta);
 
+        let has_other_liabilities = self.has_other_liabilities;
+        let has_other_isolated_liabilities = self.has_other_isolated_liabilities;
         let balance = &mut self.balance;
         let bank = &mut self.bank;
 
         let current_asset_shares = balance.asset_shares;
         let current_asset_amount = bank.get_asset_amount(current_asset_shares)? as i64;
 
-        msg!("current_asset_amount: {}, balance_delta: {}", current_asset_amount, balance_delta);
+        debug!("current_asset_amount: {}, balance_delta: {}", current_asset_amount, balance_delta);
 
         let (asset_amount_decrease, liability_amount_increase) = (
             min(current_asset_amount, balance_delta),
@@ -512,6 +1247,15 @@ impl<'a> BankAccountWrapper<'a> {
         balance.change_asset_shares(-(asset_shares_decrease as i64))?;
         bank.change_asset_shares(-(asset_shares_decrease as i64))?;
 
+        if liability_amount_increase > 0 {
+            require!(bank.config.is_borrowable(), OrbitlenError::BankNotBorrowable);
+            require!(
+                !((bank.config.is_isolated() && has_other_liabilities) ||
+                    has_other_isolated_liabilities),
+                OrbitlenError::IsolatedAccountIllegalState
+            );
+        }
+
         let liability_shares_increase = bank.get_liability_shares(
             liability_amount_increase as u64
         )?;
@@ -586,3 +1330,301 @@ impl BankVaultType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    fn adaptive_interest_rate_config() -> InterestRateConfig {
+        InterestRateConfig {
+            optimal_utilization_rate: 80,
+            curve_type: InterestRateCurveType::Adaptive as u8,
+            adaptive_config: AdaptiveRateConfig {
+                initial_rate_at_target_bps: 1_000,
+                min_rate_at_target_bps: 100,
+                max_rate_at_target_bps: 20_000,
+                kp: 0.05,
+                ki: 5.0,
+            },
+            ..Default::default()
+        }
+    }
+
+    fn multi_kink_interest_rate_config() -> InterestRateConfig {
+        let mut kinks = [InterestRateKink::default(); MAX_INTEREST_RATE_KINKS];
+        kinks[0] = InterestRateKink { utilization_rate_bps: 5_000, interest_rate_bps: 500 };
+        kinks[1] = InterestRateKink { utilization_rate_bps: 8_000, interest_rate_bps: 1_500 };
+
+        InterestRateConfig {
+            curve_type: InterestRateCurveType::MultiKink as u8,
+            base_interest_rate_bps: 100,
+            kink_count: 2,
+            kinks,
+            max_interest_rate_bps: 10_000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn multi_kink_curve_interpolates_between_kinks() {
+        let config = multi_kink_interest_rate_config();
+        config.validate().unwrap();
+        let state = InterestRateState::default();
+
+        let borrowing_rate = |ur: f32| config.calc_interest_rate(ur, &state).unwrap().1;
+
+        assert!((borrowing_rate(0.0) - 0.01).abs() < 1e-6);
+        assert!((borrowing_rate(0.25) - 0.03).abs() < 1e-6);
+        assert!((borrowing_rate(0.5) - 0.05).abs() < 1e-6);
+        assert!((borrowing_rate(0.65) - 0.1).abs() < 1e-6);
+        assert!((borrowing_rate(0.8) - 0.15).abs() < 1e-6);
+        assert!((borrowing_rate(0.9) - 0.575).abs() < 1e-6);
+        assert!((borrowing_rate(1.0) - 1.0).abs() < 1e-6);
+
+        // Lenders earn the borrowing rate scaled by utilization
+        let (lending_rate, _) = config.calc_interest_rate(0.5, &state).unwrap();
+        assert!((lending_rate - 0.025).abs() < 1e-6);
+    }
+
+    #[test]
+    fn multi_kink_curve_without_kinks_is_linear() {
+        let config = InterestRateConfig {
+            kink_count: 0,
+            ..multi_kink_interest_rate_config()
+        };
+        config.validate().unwrap();
+
+        let (_, borrowing_rate) = config
+            .calc_interest_rate(0.5, &InterestRateState::default())
+            .unwrap();
+        assert!((borrowing_rate - 0.505).abs() < 1e-6);
+    }
+
+    #[test]
+    fn multi_kink_validation_rejects_invalid_kinks() {
+        let mut config = multi_kink_interest_rate_config();
+        config.kinks[1].utilization_rate_bps = 5_000;
+        assert!(config.validate().is_err());
+
+        let mut config = multi_kink_interest_rate_config();
+        config.kinks[1].utilization_rate_bps = 10_000;
+        assert!(config.validate().is_err());
+
+        let mut config = multi_kink_interest_rate_config();
+        config.kink_count = (MAX_INTEREST_RATE_KINKS as u8) + 1;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn multi_kink_validation_rejects_decreasing_rates() {
+        let mut config = multi_kink_interest_rate_config();
+        config.kinks[0].interest_rate_bps = 50;
+        assert!(config.validate().is_err());
+
+        let mut config = multi_kink_interest_rate_config();
+        config.kinks[1].interest_rate_bps = 400;
+        assert!(config.validate().is_err());
+
+        let mut config = multi_kink_interest_rate_config();
+        config.max_interest_rate_bps = 1_000;
+        assert!(config.validate().is_err());
+
+        // Flat segments are fine
+        let mut config = multi_kink_interest_rate_config();
+        config.kinks[1].interest_rate_bps = 500;
+        config.max_interest_rate_bps = 500;
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn adaptive_rate_tracks_synthetic_utilization_series() {
+        let config = adaptive_interest_rate_config();
+        config.validate().unwrap();
+
+        let mut state = InterestRateState::new(&config);
+        let hour = 3_600;
+
+        // A week above target pushes the rate at target up on every step
+        let mut prev_rate_at_target = state.rate_at_target;
+        for _ in 0..24 * 7 {
+            state.update(&config, 0.95, hour);
+            let rate_at_target = state.rate_at_target;
+            assert!(rate_at_target >= prev_rate_at_target);
+            prev_rate_at_target = rate_at_target;
+        }
+        let elevated_rate_at_target = state.rate_at_target;
+        assert!(elevated_rate_at_target > 0.1);
+
+        // Sitting exactly on target keeps it in place
+        state.update(&config, 0.8, hour);
+        let on_target_rate_at_target = state.rate_at_target;
+        for _ in 0..24 {
+            state.update(&config, 0.8, hour);
+            let rate_at_target = state.rate_at_target;
+            assert_eq!(rate_at_target, on_target_rate_at_target);
+        }
+
+        // A week below target brings it back down
+        for _ in 0..24 * 7 {
+            state.update(&config, 0.2, hour);
+        }
+        let depressed_rate_at_target = state.rate_at_target;
+        assert!(depressed_rate_at_target < on_target_rate_at_target);
+    }
+
+    #[test]
+    fn adaptive_rate_stays_within_bounds() {
+        let config = adaptive_interest_rate_config();
+        let mut state = InterestRateState::new(&config);
+        let (min, max) = (0.01, 2.0);
+
+        for step in 0..10_000u64 {
+            // Synthetic series sweeping the whole utilization range with long saturated stretches
+            let utilization_ratio = if (step / 1_000) % 2 == 0 { 1.0 } else { 0.0 };
+            state.update(&config, utilization_ratio, 86_400);
+
+            let rate_at_target = state.rate_at_target;
+            assert!(rate_at_target >= min && rate_at_target <= max);
+        }
+    }
+
+    #[test]
+    fn adaptive_curve_pivots_around_rate_at_target() {
+        let config = adaptive_interest_rate_config();
+        let state = InterestRateState {
+            rate_at_target: 0.1,
+            last_error: 0.0,
+        };
+
+        let (_, at_zero) = config.calc_interest_rate(0.0, &state).unwrap();
+        let (_, at_target) = config.calc_interest_rate(0.8, &state).unwrap();
+        let (_, at_full) = config.calc_interest_rate(1.0, &state).unwrap();
+
+        assert!((at_zero - 0.1 / ADAPTIVE_RATE_CURVE_STEEPNESS).abs() < 1e-6);
+        assert!((at_target - 0.1).abs() < 1e-6);
+        assert!((at_full - 0.1 * ADAPTIVE_RATE_CURVE_STEEPNESS).abs() < 1e-6);
+    }
+
+    fn risk_tier_bank(risk_tier: RiskTier) -> Bank {
+        let config = BankConfig { risk_tier: risk_tier as u8, ..Default::default() };
+        let mut bank = Bank::new(Pubkey::new_unique(), 6, config, 0, Pubkey::default(), 0, 0);
+        bank.total_asset_shares = 1_000_000;
+
+        bank
+    }
+
+    /// Account owing `liability_shares` in a bank of `risk_tier`, with an empty balance in
+    /// `borrow_bank_pk` to borrow into
+    fn account_with_liability(risk_tier: RiskTier, borrow_bank_pk: Pubkey) -> LendingAccount {
+        let mut lending_account = OrbitlenAccount::zeroed().lending_account;
+        lending_account.balances[0] = Balance {
+            bank_pk: Pubkey::new_unique(),
+            asset_shares: 0,
+            liability_shares: 1_000,
+            last_update: 0,
+            risk_tier: risk_tier as u8,
+        };
+        lending_account.balances[1].bank_pk = borrow_bank_pk;
+
+        lending_account
+    }
+
+    fn borrow(bank_pk: &Pubkey, bank: &mut Bank, lending_account: &mut LendingAccount) -> Result<()> {
+        BankAccountWrapper::find(bank_pk, bank, lending_account)?.borrow(100)
+    }
+
+    #[test]
+    fn isolated_bank_rejects_borrow_next_to_other_liability() {
+        let bank_pk = Pubkey::new_unique();
+        let mut bank = risk_tier_bank(RiskTier::Isolated);
+        let mut lending_account = account_with_liability(RiskTier::Collateral, bank_pk);
+
+        assert!(lending_account.has_other_liabilities(&bank_pk));
+        assert!(!lending_account.has_other_isolated_liabilities(&bank_pk));
+        assert_eq!(
+            borrow(&bank_pk, &mut bank, &mut lending_account),
+            err!(OrbitlenError::IsolatedAccountIllegalState)
+        );
+    }
+
+    #[test]
+    fn isolated_liability_rejects_borrow_from_other_bank() {
+        let bank_pk = Pubkey::new_unique();
+        let mut bank = risk_tier_bank(RiskTier::Collateral);
+        let mut lending_account = account_with_liability(RiskTier::Isolated, bank_pk);
+
+        assert!(lending_account.has_other_isolated_liabilities(&bank_pk));
+        assert_eq!(
+            borrow(&bank_pk, &mut bank, &mut lending_account),
+            err!(OrbitlenError::IsolatedAccountIllegalState)
+        );
+    }
+
+    #[test]
+    fn isolated_liability_can_grow_on_its_own() {
+        let bank_pk = Pubkey::new_unique();
+        let mut bank = risk_tier_bank(RiskTier::Isolated);
+        let mut lending_account = account_with_liability(RiskTier::Isolated, bank_pk);
+        lending_account.balances[0].bank_pk = bank_pk;
+        lending_account.balances[1].bank_pk = Pubkey::default();
+
+        assert!(!lending_account.has_other_isolated_liabilities(&bank_pk));
+        assert!(borrow(&bank_pk, &mut bank, &mut lending_account).is_ok());
+        let liability_shares = lending_account.balances[0].liability_shares;
+        assert_eq!(liability_shares, 1_100);
+    }
+
+    #[test]
+    fn current_interest_event_reports_an_accrued_bank() {
+        let config = BankConfig {
+            interest_rate_config: InterestRateConfig {
+                optimal_utilization_rate: 80,
+                plateau_interest_rate: 10,
+                max_interest_rate: 100,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut bank = Bank::new(Pubkey::new_unique(), 6, config, 0, Pubkey::default(), 0, 0);
+        bank.asset_share_value = 1_000_000;
+        bank.liability_share_value = 1_000_000;
+        bank.total_asset_shares = 1_000;
+        bank.total_liability_shares = 400;
+
+        let bank_pk = Pubkey::new_unique();
+        // a second accrual in the same second moves nothing and reports nothing
+        assert!(bank.apply_interest_accrual(0, &bank_pk).unwrap().is_none());
+
+        let event = bank.get_current_interest_event(&bank_pk).unwrap();
+        assert_eq!(event.time_delta, 0);
+        assert_eq!(event.total_assets, 1_000_000_000);
+        assert_eq!(event.total_liabilities, 400_000_000);
+        assert_eq!(event.utilization_rate, 0.4f32 as f64);
+        assert_eq!(event.new_asset_share_value, 1_000_000);
+        assert_eq!(event.new_liability_share_value, 1_000_000);
+        // 10% * 40 / 80 borrowing, scaled by the utilization for lending
+        assert!((event.borrowing_apr - 0.05).abs() < 1e-6);
+        assert!((event.lending_apr - 0.02).abs() < 1e-6);
+        assert_eq!(event.fees, 0);
+    }
+
+    #[test]
+    fn liquidation_bonus_follows_liquidation_mode() {
+        let mut config = BankConfig {
+            liquidation_max_bonus_bps: 500,
+            liquidation_auction_slots: 100,
+            ..Default::default()
+        };
+
+        config.liquidation_mode = LiquidationMode::Fixed as u8;
+        assert_eq!(config.get_liquidation_bonus_bps(0), 500);
+        assert_eq!(config.get_liquidation_bonus_bps(50), 500);
+
+        config.liquidation_mode = LiquidationMode::Auction as u8;
+        assert_eq!(config.get_liquidation_bonus_bps(0), 0);
+        assert_eq!(config.get_liquidation_bonus_bps(50), 250);
+        assert_eq!(config.get_liquidation_bonus_bps(100), 500);
+        assert_eq!(config.get_liquidation_bonus_bps(u64::MAX), 500);
+    }
+}
@@ -0,0 +1,59 @@
+use anchor_lang::prelude::*;
+use crate::{ constants::*, error::OrbitlenError };
+
+/// Efficiency mode of correlated banks, e.g. stablecoins or LSTs against SOL. Banks list
+/// themselves in a category through `BankConfig::emode_category`, an account opted into the
+/// category whose balances are all in banks of that category is valued with its weights
+/// instead of the bank ones.
+#[account(zero_copy(unsafe))]
+#[derive(Debug, PartialEq, Default, InitSpace)]
+pub struct EmodeCategory {
+    pub category: u8,
+    pub config: EmodeConfig,
+}
+
+/// Weights of an e-mode category, in basis points
+#[zero_copy(unsafe)]
+#[derive(PartialEq, Debug, InitSpace, Default)]
+pub struct EmodeConfig {
+    pub asset_weight_init_bps: u16,
+    pub asset_weight_maint_bps: u16,
+    pub liability_weight_init_bps: u16,
+    pub liability_weight_maint_bps: u16,
+}
+
+impl EmodeConfig {
+    pub fn validate(&self) -> Result<()> {
+        require!(
+            self.asset_weight_init_bps <= self.asset_weight_maint_bps &&
+                (self.asset_weight_maint_bps as u32) <= BASIS_POINTS,
+            OrbitlenError::InvalidEmodeConfig
+        );
+        require!(
+            self.liability_weight_init_bps >= self.liability_weight_maint_bps &&
+                (self.liability_weight_maint_bps as u32) >= BASIS_POINTS,
+            OrbitlenError::InvalidEmodeConfig
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(PartialEq, AnchorDeserialize, AnchorSerialize, Debug)]
+pub struct EmodeConfigCompact {
+    pub asset_weight_init_bps: u16,
+    pub asset_weight_maint_bps: u16,
+    pub liability_weight_init_bps: u16,
+    pub liability_weight_maint_bps: u16,
+}
+
+impl From<EmodeConfigCompact> for EmodeConfig {
+    fn from(config: EmodeConfigCompact) -> Self {
+        Self {
+            asset_weight_init_bps: config.asset_weight_init_bps,
+            asset_weight_maint_bps: config.asset_weight_maint_bps,
+            liability_weight_init_bps: config.liability_weight_init_bps,
+            liability_weight_maint_bps: config.liability_weight_maint_bps,
+        }
+    }
+}
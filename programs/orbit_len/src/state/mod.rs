@@ -1,7 +1,13 @@
 pub mod account;
 pub mod bank;
+pub mod emode_category;
 pub mod price;
+pub mod raydium_amm;
+pub mod risk_engine;
 
 pub use account::*;
 pub use bank::*;
-pub use price::*;
\ No newline at end of file
+pub use emode_category::*;
+pub use price::*;
+pub use raydium_amm::*;
+pub use risk_engine::*;
\ No newline at end of file
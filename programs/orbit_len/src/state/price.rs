@@ -1,38 +1,174 @@
-use anchor_lang::prelude::*;
+use anchor_lang::{ prelude::*, Discriminator };
 use switchboard_on_demand::on_demand::accounts::pull_feed::PullFeedAccountData;
 use rust_decimal::Decimal;
+use raydium_cpmm_cpi::states::PoolState;
 
-use crate::error::OrbitlenError;
+use crate::{ error::OrbitlenError, math, utils };
 
-use super::BankConfig;
+use super::{ Bank, BankConfig, BankType, RaydiumAmmInfo };
+
+pub use crate::math::as_ui_amount;
 
 pub fn fetch_feed_price<'info>(feed: &AccountInfo<'_>, bank_config: &BankConfig) -> Result<f64> {
-    require_keys_eq!(*feed.key, bank_config.feed_data_key, OrbitlenError::InvalidPriceFeedPk);
+    fetch_pull_feed_price(feed, &bank_config.feed_data_key)
+}
+
+fn fetch_pull_feed_price(feed: &AccountInfo<'_>, feed_data_key: &Pubkey) -> Result<f64> {
+    require_keys_eq!(*feed.key, *feed_data_key, OrbitlenError::InvalidPriceFeedPk);
     let feed_account = feed.data.borrow();
     let feed = PullFeedAccountData::parse(feed_account).unwrap();
     let value = feed.value().unwrap_or(Decimal::ZERO);
-    msg!("The {} value is: {:?}", bank_config.feed_data_key, value);
+    debug!("The {} value is: {:?}", feed_data_key, value);
     value.try_into().map_err(|_| OrbitlenError::FetchPriceFailed.into())
 }
 
-pub fn calc_amount(value: u64, price: f64, mint_decimals: u8) -> Result<u64> {
-    let qt = value
-        .checked_mul(mint_decimals as u64)
-        .ok_or(OrbitlenError::MathError)?
-        .checked_div(price as u64)
-        .ok_or(OrbitlenError::MathError)?;
+/// Price of one whole bank token, `oracle_ais` must hold `get_oracle_ais_len()` accounts.
+pub fn fetch_bank_price(bank: &Bank, oracle_ais: &[AccountInfo<'_>]) -> Result<f64> {
+    require_eq!(
+        oracle_ais.len(),
+        bank.config.get_oracle_ais_len(),
+        OrbitlenError::MissingOracleAccounts
+    );
 
-    Ok(qt)
+    match bank.config.get_bank_type() {
+        Some(BankType::Standard) => fetch_feed_price(&oracle_ais[0], &bank.config),
+        Some(BankType::RaydiumLp) => fetch_raydium_lp_price(bank, oracle_ais),
+        Some(BankType::RaydiumCpmmLp) => fetch_raydium_cpmm_lp_price(bank, oracle_ais),
+        None => err!(OrbitlenError::InvalidBankConfig),
+    }
 }
 
-pub fn calc_value(amount: u64, price: f64, mint_decimals: u8) -> Result<u64> {
-    msg!("amount: {}, price: {}", amount, price);
+/// Fair value of one LP token from the pool invariant and both legs' feeds, see
+/// `math::lp_token_price`. Swaps and donations can't raise it above the value of the pool.
+///
+/// Expected oracle account schema
+/// [
+///    coin_feed_ai,
+///    pc_feed_ai,
+///    amm_ai,
+///    amm_open_orders_ai,
+///    amm_coin_vault_ai,
+///    amm_pc_vault_ai,
+///    lp_mint_ai,
+///  ]
+fn fetch_raydium_lp_price(bank: &Bank, oracle_ais: &[AccountInfo<'_>]) -> Result<f64> {
+    let lp_config = bank.config.raydium_lp_config;
+
+    let coin_price = fetch_pull_feed_price(&oracle_ais[0], &lp_config.coin_feed_data_key)?;
+    let pc_price = fetch_pull_feed_price(&oracle_ais[1], &lp_config.pc_feed_data_key)?;
 
-    let value = amount
-        .checked_mul(price as u64)
-        .ok_or(OrbitlenError::MathError)?
-        .checked_div(mint_decimals as u64)
+    for (oracle_ai, key) in oracle_ais[2..].iter().zip([
+        lp_config.amm,
+        lp_config.amm_open_orders,
+        lp_config.amm_coin_vault,
+        lp_config.amm_pc_vault,
+        bank.mint,
+    ]) {
+        require_keys_eq!(*oracle_ai.key, key, OrbitlenError::InvalidRaydiumPoolAccount);
+    }
+
+    let amm_info = RaydiumAmmInfo::load(&oracle_ais[2])?;
+    let (coin_reserve, pc_reserve) = amm_info.total_reserves(
+        &oracle_ais[3],
+        &oracle_ais[4],
+        &oracle_ais[5]
+    )?;
+    let lp_supply = utils::get_mint_supply(&oracle_ais[6])?;
+
+    debug!(
+        "coin_reserve: {}, pc_reserve: {}, lp_supply: {}",
+        coin_reserve,
+        pc_reserve,
+        lp_supply
+    );
+
+    Ok(
+        math::lp_token_price(
+            math::token_value(coin_reserve, lp_config.coin_mint_decimals, coin_price),
+            math::token_value(pc_reserve, lp_config.pc_mint_decimals, pc_price),
+            lp_supply,
+            bank.mint_decimals
+        )
+    )
+}
+
+/// Fair value of one Raydium CPMM LP token, see `math::lp_token_price`. The vaults also hold
+/// the protocol and fund fees owed to Raydium, which are not backing the LP tokens.
+///
+/// Expected oracle account schema
+/// [
+///    token_0_feed_ai,
+///    token_1_feed_ai,
+///    pool_state_ai,
+///    token_0_vault_ai,
+///    token_1_vault_ai,
+///  ]
+fn fetch_raydium_cpmm_lp_price(bank: &Bank, oracle_ais: &[AccountInfo<'_>]) -> Result<f64> {
+    let lp_config = bank.config.raydium_cpmm_lp_config;
+
+    let token_0_price = fetch_pull_feed_price(&oracle_ais[0], &lp_config.token_0_feed_data_key)?;
+    let token_1_price = fetch_pull_feed_price(&oracle_ais[1], &lp_config.token_1_feed_data_key)?;
+
+    for (oracle_ai, key) in oracle_ais[2..].iter().zip([
+        lp_config.pool_state,
+        lp_config.token_0_vault,
+        lp_config.token_1_vault,
+    ]) {
+        require_keys_eq!(*oracle_ai.key, key, OrbitlenError::InvalidRaydiumPoolAccount);
+    }
+
+    require_keys_eq!(
+        *oracle_ais[2].owner,
+        raydium_cpmm_cpi::ID,
+        OrbitlenError::InvalidRaydiumPoolAccount
+    );
+    let pool_state_data = oracle_ais[2].try_borrow_data()?;
+    require!(
+        pool_state_data.len() >= 8 + std::mem::size_of::<PoolState>() &&
+            pool_state_data[..8] == PoolState::DISCRIMINATOR,
+        OrbitlenError::InvalidRaydiumPoolAccount
+    );
+    let pool_state: PoolState = *bytemuck::from_bytes(
+        &pool_state_data[8..8 + std::mem::size_of::<PoolState>()]
+    );
+    let lp_supply = pool_state.lp_supply;
+
+    require_keys_eq!(pool_state.lp_mint, bank.mint, OrbitlenError::InvalidRaydiumPoolAccount);
+    require_keys_eq!(
+        pool_state.token_0_vault,
+        lp_config.token_0_vault,
+        OrbitlenError::InvalidRaydiumPoolAccount
+    );
+    require_keys_eq!(
+        pool_state.token_1_vault,
+        lp_config.token_1_vault,
+        OrbitlenError::InvalidRaydiumPoolAccount
+    );
+
+    let token_0_reserve = utils
+        ::get_token_account_amount(&oracle_ais[3])?
+        .checked_sub(pool_state.protocol_fees_token_0)
+        .and_then(|reserve| reserve.checked_sub(pool_state.fund_fees_token_0))
         .ok_or(OrbitlenError::MathError)?;
+    let token_1_reserve = utils
+        ::get_token_account_amount(&oracle_ais[4])?
+        .checked_sub(pool_state.protocol_fees_token_1)
+        .and_then(|reserve| reserve.checked_sub(pool_state.fund_fees_token_1))
+        .ok_or(OrbitlenError::MathError)?;
+
+    debug!(
+        "token_0_reserve: {}, token_1_reserve: {}, lp_supply: {}",
+        token_0_reserve,
+        token_1_reserve,
+        lp_supply
+    );
 
-    Ok(value)
+    Ok(
+        math::lp_token_price(
+            math::token_value(token_0_reserve, pool_state.mint_0_decimals, token_0_price),
+            math::token_value(token_1_reserve, pool_state.mint_1_decimals, token_1_price),
+            lp_supply,
+            bank.mint_decimals
+        )
+    )
 }
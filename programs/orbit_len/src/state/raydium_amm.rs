@@ -0,0 +1,210 @@
+use anchor_lang::prelude::*;
+
+use crate::{ constants::*, error::OrbitlenError, utils };
+
+/// Raydium AMM v4 `AmmInfo` account length
+const AMM_INFO_LEN: usize = 752;
+const AMM_INFO_NONCE_OFFSET: usize = 8;
+const AMM_INFO_COIN_DECIMALS_OFFSET: usize = 32;
+const AMM_INFO_PC_DECIMALS_OFFSET: usize = 40;
+const AMM_INFO_NEED_TAKE_PNL_COIN_OFFSET: usize = 192;
+const AMM_INFO_NEED_TAKE_PNL_PC_OFFSET: usize = 200;
+const AMM_INFO_COIN_VAULT_OFFSET: usize = 336;
+
+const AMM_AUTHORITY_SEED: &[u8] = b"amm authority";
+
+// OpenBook `OpenOrders` totals, after the 5 byte "serum" padding, the account flags, market
+// and owner
+const OPEN_ORDERS_NATIVE_COIN_TOTAL_OFFSET: usize = 85;
+const OPEN_ORDERS_NATIVE_PC_TOTAL_OFFSET: usize = 101;
+
+/// The fields of a Raydium AMM v4 pool the proxies check their accounts against, read from
+/// the `AmmInfo` account at their fixed offsets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaydiumAmmInfo {
+    pub nonce: u64,
+    pub coin_decimals: u64,
+    pub pc_decimals: u64,
+    /// Pool fees owed to Raydium, still held in the vaults
+    pub need_take_pnl_coin: u64,
+    pub need_take_pnl_pc: u64,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub coin_vault_mint: Pubkey,
+    pub pc_vault_mint: Pubkey,
+    pub lp_mint: Pubkey,
+    pub open_orders: Pubkey,
+    pub market: Pubkey,
+    pub market_program: Pubkey,
+    pub target_orders: Pubkey,
+}
+
+/// Pool accounts every Raydium AMM v4 proxy passes, see `RaydiumAmmInfo::load_pool`
+pub struct RaydiumPoolAccounts<'a, 'info> {
+    pub amm_program: &'a AccountInfo<'info>,
+    pub amm: &'a AccountInfo<'info>,
+    pub amm_authority: &'a AccountInfo<'info>,
+    pub amm_open_orders: &'a AccountInfo<'info>,
+    pub amm_coin_vault: &'a AccountInfo<'info>,
+    pub amm_pc_vault: &'a AccountInfo<'info>,
+    pub market: &'a AccountInfo<'info>,
+    pub coin_mint: AccountInfo<'info>,
+    pub pc_mint: Option<AccountInfo<'info>>,
+}
+
+impl RaydiumAmmInfo {
+    /// Reads the pool state and checks the accounts shared by every proxy against it, so
+    /// borrowed tokens can only reach a whitelisted AMM program and pool holding the bank
+    /// mints. Proxies check their other pool accounts against the returned state.
+    pub fn load_pool(pool_accounts: &RaydiumPoolAccounts) -> Result<Self> {
+        let amm_info = Self::load_checked(pool_accounts.amm_program, pool_accounts.amm)?;
+
+        amm_info.check_authority(pool_accounts.amm_program, pool_accounts.amm_authority)?;
+        amm_info.check_vaults(pool_accounts.amm_coin_vault, pool_accounts.amm_pc_vault)?;
+        require_keys_eq!(
+            amm_info.open_orders,
+            *pool_accounts.amm_open_orders.key,
+            OrbitlenError::InvalidRaydiumPoolAccount
+        );
+        require_keys_eq!(
+            amm_info.market,
+            *pool_accounts.market.key,
+            OrbitlenError::InvalidRaydiumPoolAccount
+        );
+        amm_info.check_bank_mints(&pool_accounts.coin_mint, pool_accounts.pc_mint.as_ref())?;
+
+        Ok(amm_info)
+    }
+
+    /// Reads the pool state of `amm`, which must be owned by `amm_program`, itself one of
+    /// `RAYDIUM_AMM_PROGRAM_IDS`.
+    pub fn load_checked(amm_program: &AccountInfo, amm: &AccountInfo) -> Result<Self> {
+        require!(
+            RAYDIUM_AMM_PROGRAM_IDS.contains(amm_program.key),
+            OrbitlenError::InvalidRaydiumProgram
+        );
+        require_keys_eq!(*amm.owner, *amm_program.key, OrbitlenError::InvalidRaydiumPoolAccount);
+
+        Self::load(amm)
+    }
+
+    /// Reads the pool state of `amm` without checking its owner, for pools already pinned by a
+    /// bank config.
+    pub fn load(amm: &AccountInfo) -> Result<Self> {
+        let data = amm.try_borrow_data()?;
+        require_eq!(data.len(), AMM_INFO_LEN, OrbitlenError::InvalidRaydiumPoolAccount);
+
+        let read_pubkey = |index: usize| {
+            let offset = AMM_INFO_COIN_VAULT_OFFSET + index * 32;
+            Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+        };
+
+        let read_u64 = |offset: usize| {
+            u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+        };
+
+        Ok(RaydiumAmmInfo {
+            nonce: read_u64(AMM_INFO_NONCE_OFFSET),
+            coin_decimals: read_u64(AMM_INFO_COIN_DECIMALS_OFFSET),
+            pc_decimals: read_u64(AMM_INFO_PC_DECIMALS_OFFSET),
+            need_take_pnl_coin: read_u64(AMM_INFO_NEED_TAKE_PNL_COIN_OFFSET),
+            need_take_pnl_pc: read_u64(AMM_INFO_NEED_TAKE_PNL_PC_OFFSET),
+            coin_vault: read_pubkey(0),
+            pc_vault: read_pubkey(1),
+            coin_vault_mint: read_pubkey(2),
+            pc_vault_mint: read_pubkey(3),
+            lp_mint: read_pubkey(4),
+            open_orders: read_pubkey(5),
+            market: read_pubkey(6),
+            market_program: read_pubkey(7),
+            target_orders: read_pubkey(8),
+        })
+    }
+
+    pub fn check_authority(&self, amm_program: &AccountInfo, amm_authority: &AccountInfo) -> Result<()> {
+        let nonce = u8::try_from(self.nonce).map_err(|_| OrbitlenError::InvalidRaydiumPoolAccount)?;
+        let authority = Pubkey::create_program_address(
+            &[AMM_AUTHORITY_SEED, &[nonce]],
+            amm_program.key
+        ).map_err(|_| OrbitlenError::InvalidRaydiumPoolAccount)?;
+
+        require_keys_eq!(authority, *amm_authority.key, OrbitlenError::InvalidRaydiumPoolAccount);
+
+        Ok(())
+    }
+
+    pub fn check_vaults(&self, amm_coin_vault: &AccountInfo, amm_pc_vault: &AccountInfo) -> Result<()> {
+        require_keys_eq!(self.coin_vault, *amm_coin_vault.key, OrbitlenError::InvalidRaydiumPoolAccount);
+        require_keys_eq!(self.pc_vault, *amm_pc_vault.key, OrbitlenError::InvalidRaydiumPoolAccount);
+
+        Ok(())
+    }
+
+    /// The coin bank mint must be one of the pool mints, and the pc bank mint when given the
+    /// other one. AMM v4 pools only move SPL Token mints, so Token-2022 bank mints are rejected
+    /// here rather than failing inside the Raydium CPI.
+    pub fn check_bank_mints(&self, coin_mint: &AccountInfo, pc_mint: Option<&AccountInfo>) -> Result<()> {
+        require_keys_eq!(*coin_mint.owner, anchor_spl::token::ID, OrbitlenError::UnsupportedTokenProgram);
+
+        let other_mint = if *coin_mint.key == self.coin_vault_mint {
+            self.pc_vault_mint
+        } else if *coin_mint.key == self.pc_vault_mint {
+            self.coin_vault_mint
+        } else {
+            return err!(OrbitlenError::InvalidRaydiumPoolAccount);
+        };
+
+        if let Some(pc_mint) = pc_mint {
+            require_keys_eq!(*pc_mint.owner, anchor_spl::token::ID, OrbitlenError::UnsupportedTokenProgram);
+            require_keys_eq!(*pc_mint.key, other_mint, OrbitlenError::InvalidRaydiumPoolAccount);
+        }
+
+        Ok(())
+    }
+
+    /// Coin and pc reserves the pool trades against: the vaults plus the tokens resting in its
+    /// OpenBook open orders, minus the fees owed to Raydium. This is what Raydium itself prices
+    /// swaps and LP tokens with.
+    pub fn total_reserves(
+        &self,
+        amm_open_orders: &AccountInfo,
+        amm_coin_vault: &AccountInfo,
+        amm_pc_vault: &AccountInfo
+    ) -> Result<(u64, u64)> {
+        self.check_vaults(amm_coin_vault, amm_pc_vault)?;
+        require_keys_eq!(self.open_orders, *amm_open_orders.key, OrbitlenError::InvalidRaydiumPoolAccount);
+        require_keys_eq!(
+            *amm_open_orders.owner,
+            self.market_program,
+            OrbitlenError::InvalidRaydiumPoolAccount
+        );
+
+        let (open_orders_coin, open_orders_pc) = {
+            let data = amm_open_orders.try_borrow_data()?;
+            require_gte!(
+                data.len(),
+                OPEN_ORDERS_NATIVE_PC_TOTAL_OFFSET + 8,
+                OrbitlenError::InvalidRaydiumPoolAccount
+            );
+
+            let read_u64 = |offset: usize| {
+                u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+            };
+
+            (read_u64(OPEN_ORDERS_NATIVE_COIN_TOTAL_OFFSET), read_u64(OPEN_ORDERS_NATIVE_PC_TOTAL_OFFSET))
+        };
+
+        let coin_reserve = utils
+            ::get_token_account_amount(amm_coin_vault)?
+            .checked_add(open_orders_coin)
+            .and_then(|reserve| reserve.checked_sub(self.need_take_pnl_coin))
+            .ok_or(OrbitlenError::MathError)?;
+        let pc_reserve = utils
+            ::get_token_account_amount(amm_pc_vault)?
+            .checked_add(open_orders_pc)
+            .and_then(|reserve| reserve.checked_sub(self.need_take_pnl_pc))
+            .ok_or(OrbitlenError::MathError)?;
+
+        Ok((coin_reserve, pc_reserve))
+    }
+}
@@ -0,0 +1,474 @@
+use anchor_lang::prelude::*;
+use crate::{ constants::*, error::OrbitlenError, math::{ self, bps_as_weight } };
+
+use super::{
+    fetch_bank_price,
+    Balance,
+    Bank,
+    BankConfig,
+    EmodeCategory,
+    EmodeConfig,
+    OrbitlenAccount,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RiskRequirementType {
+    /// Taking on new risk, e.g. borrowing or trading through a proxy
+    Initial,
+    /// Staying clear of liquidation
+    Maintenance,
+}
+
+impl RiskRequirementType {
+    /// Asset weight of the bank, or of the e-mode category when `emode_config` is set
+    pub fn get_asset_weight(&self, config: &BankConfig, emode_config: Option<&EmodeConfig>) -> f64 {
+        let weight_bps = match (self, emode_config) {
+            (RiskRequirementType::Initial, None) => config.asset_weight_init_bps,
+            (RiskRequirementType::Maintenance, None) => config.asset_weight_maint_bps,
+            (RiskRequirementType::Initial, Some(emode_config)) =>
+                emode_config.asset_weight_init_bps,
+            (RiskRequirementType::Maintenance, Some(emode_config)) =>
+                emode_config.asset_weight_maint_bps,
+        };
+
+        bps_as_weight(weight_bps)
+    }
+
+    /// Liability weight of the bank, or of the e-mode category when `emode_config` is set
+    pub fn get_liability_weight(
+        &self,
+        config: &BankConfig,
+        emode_config: Option<&EmodeConfig>
+    ) -> f64 {
+        let weight_bps = match (self, emode_config) {
+            (RiskRequirementType::Initial, None) => config.liability_weight_init_bps,
+            (RiskRequirementType::Maintenance, None) => config.liability_weight_maint_bps,
+            (RiskRequirementType::Initial, Some(emode_config)) =>
+                emode_config.liability_weight_init_bps,
+            (RiskRequirementType::Maintenance, Some(emode_config)) =>
+                emode_config.liability_weight_maint_bps,
+        };
+
+        bps_as_weight(weight_bps)
+    }
+}
+
+/// Weighted value of all assets and liabilities of an account, in feed currency
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AccountHealth {
+    pub assets: f64,
+    pub liabilities: f64,
+    /// Number of balances holding a liability, and how many of them are in isolated banks
+    pub liabilities_count: usize,
+    pub isolated_liabilities_count: usize,
+    /// Whether the e-mode weights of the account's category were applied
+    pub emode: bool,
+}
+
+impl AccountHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.assets >= self.liabilities
+    }
+
+    /// Whether the account is healthy by more than `buffer_bps` of its weighted liabilities
+    pub fn exceeds_buffer(&self, buffer_bps: u32) -> bool {
+        math::exceeds_buffer(self.assets, self.liabilities, buffer_bps)
+    }
+
+    /// An isolated liability has to be the only liability of the account
+    pub fn check_risk_tiers(&self) -> Result<()> {
+        require!(
+            self.isolated_liabilities_count == 0 || self.liabilities_count == 1,
+            OrbitlenError::IsolatedAccountIllegalState
+        );
+
+        Ok(())
+    }
+}
+
+/// Unweighted value of one active balance, in feed currency
+#[derive(Clone, Copy, Debug)]
+pub struct BalanceValue {
+    pub bank_pk: Pubkey,
+    pub mint: Pubkey,
+    pub config: BankConfig,
+    pub liability_shares: u64,
+    pub asset_amount: u64,
+    pub liability_amount: u64,
+    pub price: f64,
+    pub asset_value: f64,
+    pub liability_value: f64,
+}
+
+pub struct RiskEngine;
+
+impl RiskEngine {
+    /// Values every active balance of the account, with the e-mode weights when the account
+    /// opted into a category and all of its banks are in it.
+    ///
+    /// Expected remaining account schema at the front of `remaining_ais`, the e-mode category
+    /// account only when the account opted into one, then one group per active balance in
+    /// any order
+    /// [
+    ///    emode_category_ai,
+    ///    bank_ai,
+    ///    ...bank_oracle_ais,
+    ///  ]
+    ///
+    /// A bank's oracle accounts are the ones `fetch_bank_price` expects.
+    pub fn calc_account_health<'info>(
+        orbitlen_account: &OrbitlenAccount,
+        remaining_ais: &'info [AccountInfo<'info>],
+        requirement_type: RiskRequirementType
+    ) -> Result<AccountHealth> {
+        let (emode_config, bank_ais) = Self::load_emode_config(orbitlen_account, remaining_ais)?;
+        let balance_values = Self::calc_balance_values(orbitlen_account, bank_ais)?;
+
+        Ok(
+            Self::weigh_balance_values(
+                orbitlen_account,
+                emode_config.as_ref(),
+                &balance_values,
+                requirement_type
+            )
+        )
+    }
+
+    /// Loads the weights of the account's e-mode category from the front of `remaining_ais`,
+    /// and returns them with the accounts after it. Accounts outside of e-mode pass none.
+    pub fn load_emode_config<'info>(
+        orbitlen_account: &OrbitlenAccount,
+        remaining_ais: &'info [AccountInfo<'info>]
+    ) -> Result<(Option<EmodeConfig>, &'info [AccountInfo<'info>])> {
+        if orbitlen_account.emode_category == EMODE_CATEGORY_NONE {
+            return Ok((None, remaining_ais));
+        }
+
+        let (emode_category_ai, bank_ais) = remaining_ais
+            .split_first()
+            .ok_or(OrbitlenError::InvalidEmodeCategory)?;
+        let emode_category_loader = AccountLoader::<EmodeCategory>::try_from(emode_category_ai)?;
+        let emode_category = emode_category_loader.load()?;
+        require_eq!(
+            emode_category.category,
+            orbitlen_account.emode_category,
+            OrbitlenError::InvalidEmodeCategory
+        );
+
+        Ok((Some(emode_category.config), bank_ais))
+    }
+
+    /// Prices every active balance of the account, `remaining_ais` holds the bank groups of
+    /// `calc_account_health` without the e-mode category account
+    pub fn calc_balance_values<'info>(
+        orbitlen_account: &OrbitlenAccount,
+        remaining_ais: &'info [AccountInfo<'info>]
+    ) -> Result<Vec<BalanceValue>> {
+        let balances = &orbitlen_account.lending_account.balances;
+        let active_balances_count = balances
+            .iter()
+            .filter(|balance| balance.bank_pk != Pubkey::default())
+            .count();
+
+        let mut balance_values: Vec<BalanceValue> = Vec::with_capacity(active_balances_count);
+        let mut ais = remaining_ais;
+
+        for _ in 0..active_balances_count {
+            let (bank_ai, rest) = ais
+                .split_first()
+                .ok_or(OrbitlenError::InvalidHealthAccounts)?;
+
+            let balance = balances
+                .iter()
+                .find(|balance| balance.bank_pk == *bank_ai.key)
+                .ok_or(OrbitlenError::InvalidHealthAccounts)?;
+            require!(
+                !balance_values.iter().any(|value| value.bank_pk == *bank_ai.key),
+                OrbitlenError::InvalidHealthAccounts
+            );
+
+            let bank_loader = AccountLoader::<Bank>::try_from(bank_ai)?;
+            let bank = bank_loader.load()?;
+
+            let oracle_ais_len = bank.config.get_oracle_ais_len();
+            let oracle_ais = rest
+                .get(..oracle_ais_len)
+                .ok_or(OrbitlenError::MissingOracleAccounts)?;
+            ais = &rest[oracle_ais_len..];
+
+            let price = fetch_bank_price(&bank, oracle_ais)?;
+
+            balance_values.push(Self::calc_balance_value(bank_ai.key, &bank, balance, price)?);
+        }
+
+        Ok(balance_values)
+    }
+
+    /// Values `balance` of `bank` at `price`, without touching any account
+    pub fn calc_balance_value(
+        bank_pk: &Pubkey,
+        bank: &Bank,
+        balance: &Balance,
+        price: f64
+    ) -> Result<BalanceValue> {
+        let asset_amount = bank.get_asset_amount(balance.asset_shares)?;
+        let liability_amount = bank.get_liability_amount(balance.liability_shares)?;
+
+        Ok(BalanceValue {
+            bank_pk: *bank_pk,
+            mint: bank.mint,
+            config: bank.config,
+            liability_shares: balance.liability_shares,
+            asset_amount,
+            liability_amount,
+            price,
+            asset_value: math::token_value(asset_amount, bank.mint_decimals, price),
+            liability_value: math::token_value(liability_amount, bank.mint_decimals, price),
+        })
+    }
+
+    /// Weighs balances priced by `calc_balance_values` for `requirement_type`. The weights of
+    /// the account's e-mode category, `emode_config`, only apply when every balance is in a
+    /// bank of that category.
+    pub fn weigh_balance_values(
+        orbitlen_account: &OrbitlenAccount,
+        emode_config: Option<&EmodeConfig>,
+        balance_values: &[BalanceValue],
+        requirement_type: RiskRequirementType
+    ) -> AccountHealth {
+        let mut health = AccountHealth::default();
+        let emode_config = emode_config.filter(|_| {
+            orbitlen_account.emode_category != EMODE_CATEGORY_NONE &&
+                balance_values
+                    .iter()
+                    .all(|value| value.config.emode_category == orbitlen_account.emode_category)
+        });
+
+        for value in balance_values {
+            let config = &value.config;
+
+            health.assets +=
+                value.asset_value * requirement_type.get_asset_weight(config, emode_config);
+            health.liabilities +=
+                value.liability_value * requirement_type.get_liability_weight(config, emode_config);
+
+            if value.liability_shares > 0 {
+                health.liabilities_count += 1;
+                if config.is_isolated() {
+                    health.isolated_liabilities_count += 1;
+                }
+            }
+        }
+
+        health.emode = emode_config.is_some();
+
+        debug!(
+            "{:?} health: assets: {}, liabilities: {}, emode: {}",
+            requirement_type,
+            health.assets,
+            health.liabilities,
+            health.emode
+        );
+
+        health
+    }
+
+    /// Fails unless the weighted assets of the account cover its weighted liabilities, and
+    /// the account holds no isolated liability next to other ones
+    pub fn check_account_health<'info>(
+        orbitlen_account: &OrbitlenAccount,
+        remaining_ais: &'info [AccountInfo<'info>],
+        requirement_type: RiskRequirementType
+    ) -> Result<()> {
+        let health = Self::calc_account_health(orbitlen_account, remaining_ais, requirement_type)?;
+
+        health.check_risk_tiers()?;
+        require!(health.is_healthy(), OrbitlenError::InsufficientAccountHealth);
+
+        Ok(())
+    }
+
+    /// Number of accounts the health groups of `banks_count` banks, after the e-mode category
+    /// account of accounts in `emode`, take up at the front of `remaining_ais`, so callers can
+    /// pass the rest on to a CPI
+    pub fn get_health_ais_len<'info>(
+        remaining_ais: &'info [AccountInfo<'info>],
+        emode: bool,
+        banks_count: usize
+    ) -> Result<usize> {
+        let mut len = emode as usize;
+
+        for _ in 0..banks_count {
+            let bank_ai = remaining_ais.get(len).ok_or(OrbitlenError::InvalidHealthAccounts)?;
+            let bank_loader = AccountLoader::<Bank>::try_from(bank_ai)?;
+
+            len += 1 + bank_loader.load()?.config.get_oracle_ais_len();
+        }
+
+        require_gte!(remaining_ais.len(), len, OrbitlenError::MissingOracleAccounts);
+
+        Ok(len)
+    }
+
+    /// Splits `remaining_ais` into the health accounts of `orbitlen_account`, counting
+    /// `extra_banks` the instruction opens a balance in, and whatever follows them
+    pub fn split_health_ais<'info>(
+        orbitlen_account: &OrbitlenAccount,
+        extra_banks: &[Pubkey],
+        remaining_ais: &'info [AccountInfo<'info>]
+    ) -> Result<(&'info [AccountInfo<'info>], &'info [AccountInfo<'info>])> {
+        let mut banks: Vec<Pubkey> = orbitlen_account.lending_account.balances
+            .iter()
+            .map(|balance| balance.bank_pk)
+            .filter(|bank_pk| *bank_pk != Pubkey::default())
+            .collect();
+
+        for bank_pk in extra_banks {
+            if !banks.contains(bank_pk) {
+                banks.push(*bank_pk);
+            }
+        }
+
+        let health_ais_len = Self::get_health_ais_len(
+            remaining_ais,
+            orbitlen_account.emode_category != EMODE_CATEGORY_NONE,
+            banks.len()
+        )?;
+
+        Ok(remaining_ais.split_at(health_ais_len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytemuck::Zeroable;
+
+    const EMODE_CATEGORY: u8 = 1;
+
+    fn emode_config() -> EmodeConfig {
+        EmodeConfig {
+            asset_weight_init_bps: 9_500,
+            asset_weight_maint_bps: 9_700,
+            liability_weight_init_bps: 10_500,
+            liability_weight_maint_bps: 10_300,
+        }
+    }
+
+    fn balance_value(emode_category: u8, asset_value: f64, liability_value: f64) -> BalanceValue {
+        BalanceValue {
+            bank_pk: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            config: BankConfig {
+                asset_weight_init_bps: 8_000,
+                asset_weight_maint_bps: 9_000,
+                liability_weight_init_bps: 12_000,
+                liability_weight_maint_bps: 11_000,
+                emode_category,
+                ..Default::default()
+            },
+            liability_shares: (liability_value > 0.0) as u64,
+            asset_amount: 0,
+            liability_amount: 0,
+            price: 1.0,
+            asset_value,
+            liability_value,
+        }
+    }
+
+    fn orbitlen_account(emode_category: u8) -> OrbitlenAccount {
+        OrbitlenAccount { emode_category, ..OrbitlenAccount::zeroed() }
+    }
+
+    fn assert_health(health: &AccountHealth, assets: f64, liabilities: f64) {
+        let (health_assets, health_liabilities) = (health.assets, health.liabilities);
+        assert!((health_assets - assets).abs() < 1e-9, "assets: {}", health_assets);
+        assert!(
+            (health_liabilities - liabilities).abs() < 1e-9,
+            "liabilities: {}",
+            health_liabilities
+        );
+    }
+
+    #[test]
+    fn category_weights_apply_when_every_bank_is_in_the_category() {
+        let balance_values = [
+            balance_value(EMODE_CATEGORY, 100.0, 0.0),
+            balance_value(EMODE_CATEGORY, 0.0, 80.0),
+        ];
+        let orbitlen_account = orbitlen_account(EMODE_CATEGORY);
+        let emode_config = emode_config();
+
+        // the bank weights leave the account under its initial margin, the category ones don't
+        let health = RiskEngine::weigh_balance_values(
+            &orbitlen_account,
+            None,
+            &balance_values,
+            RiskRequirementType::Initial
+        );
+        assert_health(&health, 80.0, 96.0);
+        assert!(!health.is_healthy());
+
+        let health = RiskEngine::weigh_balance_values(
+            &orbitlen_account,
+            Some(&emode_config),
+            &balance_values,
+            RiskRequirementType::Initial
+        );
+        assert!(health.emode);
+        assert_health(&health, 95.0, 84.0);
+        assert!(health.is_healthy());
+
+        let health = RiskEngine::weigh_balance_values(
+            &orbitlen_account,
+            Some(&emode_config),
+            &balance_values,
+            RiskRequirementType::Maintenance
+        );
+        assert_health(&health, 97.0, 82.4);
+    }
+
+    #[test]
+    fn bank_outside_the_category_falls_back_to_bank_weights() {
+        let balance_values = [
+            balance_value(EMODE_CATEGORY, 100.0, 0.0),
+            balance_value(EMODE_CATEGORY + 1, 0.0, 80.0),
+        ];
+
+        let health = RiskEngine::weigh_balance_values(
+            &orbitlen_account(EMODE_CATEGORY),
+            Some(&emode_config()),
+            &balance_values,
+            RiskRequirementType::Initial
+        );
+        assert!(!health.emode);
+        assert_health(&health, 80.0, 96.0);
+    }
+
+    #[test]
+    fn account_outside_emode_uses_bank_weights() {
+        let balance_values = [
+            balance_value(EMODE_CATEGORY, 100.0, 0.0),
+            balance_value(EMODE_CATEGORY, 0.0, 80.0),
+        ];
+
+        let health = RiskEngine::weigh_balance_values(
+            &orbitlen_account(EMODE_CATEGORY_NONE),
+            Some(&emode_config()),
+            &balance_values,
+            RiskRequirementType::Initial
+        );
+        assert!(!health.emode);
+        assert_health(&health, 80.0, 96.0);
+    }
+
+    #[test]
+    fn emode_config_rejects_weights_past_par() {
+        let validate = |emode_config: EmodeConfig| emode_config.validate().is_ok();
+
+        assert!(validate(emode_config()));
+        assert!(!validate(EmodeConfig { asset_weight_maint_bps: 10_001, ..emode_config() }));
+        assert!(!validate(EmodeConfig { liability_weight_maint_bps: 9_999, ..emode_config() }));
+        assert!(!validate(EmodeConfig { asset_weight_init_bps: 9_800, ..emode_config() }));
+    }
+}
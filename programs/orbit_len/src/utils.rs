@@ -1,6 +1,15 @@
 use crate::{ error::OrbitlenError, state::* };
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{
+        transfer_fee::TransferFeeConfig,
+        BaseStateWithExtensions,
+        ExtensionType,
+        StateWithExtensions,
+    },
+};
 
 pub fn maybe_take_bank_mint<'info>(
     remaining_accounts: &mut &'info [AccountInfo<'info>],
@@ -13,7 +22,7 @@ pub fn maybe_take_bank_mint<'info>(
                 .split_first()
                 .ok_or(OrbitlenError::MintRequired)?;
 
-            msg!("maybe_take_bank_mint: maybe_mint: {:?}", maybe_mint.key);
+            debug!("maybe_take_bank_mint: maybe_mint: {:?}", maybe_mint.key);
 
             *remaining_accounts = remaining;
 
@@ -24,6 +33,212 @@ pub fn maybe_take_bank_mint<'info>(
             InterfaceAccount::try_from(maybe_mint)
         }
 
-        _ => panic!("unsupported token program"),
+        _ => err!(OrbitlenError::UnsupportedTokenProgram),
+    }
+}
+
+/// Mint extensions a bank can be created for. Anything else either lets a third party
+/// move or freeze vault funds, or breaks the plain `transfer_checked` accounting.
+const SUPPORTED_MINT_EXTENSIONS: [ExtensionType; 9] = [
+    ExtensionType::TransferFeeConfig,
+    ExtensionType::TransferHook,
+    ExtensionType::InterestBearingConfig,
+    ExtensionType::MetadataPointer,
+    ExtensionType::TokenMetadata,
+    ExtensionType::GroupPointer,
+    ExtensionType::TokenGroup,
+    ExtensionType::GroupMemberPointer,
+    ExtensionType::TokenGroupMember,
+];
+
+pub fn validate_bank_mint_extensions(mint_ai: &AccountInfo) -> Result<()> {
+    if mint_ai.owner.eq(&anchor_spl::token::ID) {
+        return Ok(());
+    }
+
+    let mint_data = mint_ai.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+
+    for extension in mint.get_extension_types()? {
+        debug!("validate_bank_mint_extensions: extension: {:?}", extension);
+
+        if !SUPPORTED_MINT_EXTENSIONS.contains(&extension) {
+            return err!(OrbitlenError::UnsupportedMintExtension);
+        }
+    }
+
+    Ok(())
+}
+
+/// Amount the destination actually receives when `amount` is sent with `transfer_checked`,
+/// i.e. net of the Token-2022 transfer fee if the mint has one.
+pub fn calculate_post_fee_spl_deposit_amount(
+    mint_ai: AccountInfo,
+    amount: u64,
+    epoch: u64
+) -> Result<u64> {
+    if mint_ai.owner.eq(&anchor_spl::token::ID) {
+        return Ok(amount);
+    }
+
+    let mint_data = mint_ai.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+
+    let fee = if let Ok(transfer_fee_config) = mint.get_extension::<TransferFeeConfig>() {
+        transfer_fee_config.calculate_epoch_fee(epoch, amount).ok_or(OrbitlenError::MathError)?
+    } else {
+        0
+    };
+
+    debug!("calculate_post_fee_spl_deposit_amount: amount: {}, fee: {}", amount, fee);
+
+    amount.checked_sub(fee).ok_or(OrbitlenError::MathError.into())
+}
+
+/// Amount that has to be sent with `transfer_checked` for the destination to receive
+/// `post_fee_amount`, i.e. grossed up by the Token-2022 transfer fee if the mint has one.
+pub fn calculate_pre_fee_spl_deposit_amount(
+    mint_ai: AccountInfo,
+    post_fee_amount: u64,
+    epoch: u64
+) -> Result<u64> {
+    if mint_ai.owner.eq(&anchor_spl::token::ID) {
+        return Ok(post_fee_amount);
+    }
+
+    let mint_data = mint_ai.try_borrow_data()?;
+    let mint = StateWithExtensions::<spl_token_2022::state::Mint>::unpack(&mint_data)?;
+
+    let fee = if let Ok(transfer_fee_config) = mint.get_extension::<TransferFeeConfig>() {
+        transfer_fee_config
+            .calculate_inverse_epoch_fee(epoch, post_fee_amount)
+            .ok_or(OrbitlenError::MathError)?
+    } else {
+        0
+    };
+
+    debug!(
+        "calculate_pre_fee_spl_deposit_amount: post_fee_amount: {}, fee: {}",
+        post_fee_amount,
+        fee
+    );
+
+    post_fee_amount.checked_add(fee).ok_or(OrbitlenError::MathError.into())
+}
+
+pub fn get_token_account_amount(token_account_ai: &AccountInfo) -> Result<u64> {
+    require!(
+        token_account_ai.owner.eq(&anchor_spl::token::ID) ||
+            token_account_ai.owner.eq(&anchor_spl::token_2022::ID),
+        ErrorCode::AccountOwnedByWrongProgram
+    );
+
+    let data = token_account_ai.try_borrow_data()?;
+    Ok(TokenAccount::try_deserialize(&mut &data[..])?.amount)
+}
+
+pub fn get_mint_supply(mint_ai: &AccountInfo) -> Result<u64> {
+    require!(
+        mint_ai.owner.eq(&anchor_spl::token::ID) || mint_ai.owner.eq(&anchor_spl::token_2022::ID),
+        ErrorCode::AccountOwnedByWrongProgram
+    );
+
+    let data = mint_ai.try_borrow_data()?;
+    Ok(Mint::try_deserialize(&mut &data[..])?.supply)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_option::COption;
+    use anchor_spl::token_2022::spl_token_2022::extension::{
+        BaseStateWithExtensionsMut,
+        StateWithExtensionsMut,
+    };
+
+    /// Token-2022 mint data with a transfer fee of `transfer_fee_bps`, capped at `maximum_fee`
+    fn transfer_fee_mint_data(transfer_fee_bps: u16, maximum_fee: u64) -> Vec<u8> {
+        let mut mint_data = vec![
+            0u8;
+            ExtensionType::try_calculate_account_len::<spl_token_2022::state::Mint>(&[
+                ExtensionType::TransferFeeConfig,
+            ]).unwrap()
+        ];
+        let mut mint = StateWithExtensionsMut::<spl_token_2022::state::Mint>
+            ::unpack_uninitialized(&mut mint_data)
+            .unwrap();
+
+        let transfer_fee_config = mint.init_extension::<TransferFeeConfig>(true).unwrap();
+        transfer_fee_config.newer_transfer_fee.maximum_fee = maximum_fee.into();
+        transfer_fee_config.newer_transfer_fee.transfer_fee_basis_points = transfer_fee_bps.into();
+        transfer_fee_config.older_transfer_fee = transfer_fee_config.newer_transfer_fee;
+
+        mint.base = spl_token_2022::state::Mint {
+            mint_authority: COption::None,
+            supply: 0,
+            decimals: 6,
+            is_initialized: true,
+            freeze_authority: COption::None,
+        };
+        mint.pack_base();
+        mint.init_account_type().unwrap();
+
+        mint_data
+    }
+
+    fn with_mint_ai<T>(owner: &Pubkey, mint_data: &mut [u8], f: impl FnOnce(AccountInfo) -> T) -> T {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mint_ai = AccountInfo::new(&key, false, false, &mut lamports, mint_data, owner, false, 0);
+
+        f(mint_ai)
+    }
+
+    #[test]
+    fn pre_fee_amount_grosses_up_the_transfer_fee() {
+        let mut mint_data = transfer_fee_mint_data(100, u64::MAX);
+
+        with_mint_ai(&anchor_spl::token_2022::ID, &mut mint_data, |mint_ai| {
+            // 1% fee, rounded up: 1_010_102 pays 10_102 and lands exactly 1_000_000
+            let pre_fee_amount = calculate_pre_fee_spl_deposit_amount(
+                mint_ai.clone(),
+                1_000_000,
+                0
+            ).unwrap();
+            assert_eq!(pre_fee_amount, 1_010_102);
+            assert_eq!(
+                calculate_post_fee_spl_deposit_amount(mint_ai.clone(), pre_fee_amount, 0).unwrap(),
+                1_000_000
+            );
+            assert_eq!(calculate_pre_fee_spl_deposit_amount(mint_ai, 0, 0).unwrap(), 0);
+        });
+    }
+
+    #[test]
+    fn pre_fee_amount_respects_the_maximum_fee() {
+        let mut mint_data = transfer_fee_mint_data(100, 500);
+
+        with_mint_ai(&anchor_spl::token_2022::ID, &mut mint_data, |mint_ai| {
+            assert_eq!(
+                calculate_pre_fee_spl_deposit_amount(mint_ai.clone(), 1_000_000, 0).unwrap(),
+                1_000_500
+            );
+            assert_eq!(
+                calculate_post_fee_spl_deposit_amount(mint_ai, 1_000_500, 0).unwrap(),
+                1_000_000
+            );
+        });
+    }
+
+    #[test]
+    fn spl_token_mints_take_no_fee() {
+        let mut mint_data = transfer_fee_mint_data(100, u64::MAX);
+
+        with_mint_ai(&anchor_spl::token::ID, &mut mint_data, |mint_ai| {
+            assert_eq!(
+                calculate_pre_fee_spl_deposit_amount(mint_ai, 1_000_000, 0).unwrap(),
+                1_000_000
+            );
+        });
     }
 }
@@ -21,7 +21,7 @@ import {
   createAccount,
   getAssociatedTokenAddressSync,
 } from "@solana/spl-token";
-import { delay, safeAirdrop } from "./utils";
+import { delay, safeAirdrop, getHealthRemainingAccounts } from "./utils";
 import {
   getKeypairFromEnvironment,
   makeKeypairs,
@@ -164,6 +164,9 @@ describe("raydium", () => {
   let RayBank;
   let RayLiquidityVaultAuthority;
   let RayLiquidityVault;
+  let LpBank;
+  let LpLiquidityVaultAuthority;
+  let LpLiquidityVault;
   let userAOrbitlenAccount;
   let userARay, userAUSDC, userALP;
 
@@ -190,6 +193,21 @@ describe("raydium", () => {
       program.programId
     );
 
+    [LpBank] = PublicKey.findProgramAddressSync(
+      [Buffer.from("bank"), globalInfo.lpMint.toBuffer()],
+      program.programId
+    );
+
+    [LpLiquidityVaultAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("liquidity_vault_auth"), LpBank.toBuffer()],
+      program.programId
+    );
+
+    [LpLiquidityVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("liquidity_vault"), LpBank.toBuffer()],
+      program.programId
+    );
+
     [userAOrbitlenAccount] = PublicKey.findProgramAddressSync(
       [Buffer.from("orbitlen_account"), userA.publicKey.toBuffer()],
       program.programId
@@ -361,7 +379,8 @@ describe("raydium", () => {
     let tx = await program.methods
       .raydiumDeposit(
         new BN(depositAmount), // coinAmount
-        new BN(depositAmount) // pcAmount
+        new BN(depositAmount), // pcAmount
+        new BN(0) // minLpAmount
       )
       .accounts({
         ammProgram: globalInfo.ammProgram,
@@ -382,9 +401,25 @@ describe("raydium", () => {
         coinBank: RayBank,
         coinBankLiquidityVault: RayLiquidityVault,
         coinBankLiquidityVaultAuthority: RayLiquidityVaultAuthority,
+        lpBank: LpBank,
+        lpBankLiquidityVault: LpLiquidityVault,
         userOwner: userA.publicKey,
         tokenProgram: TOKEN_PROGRAM_ID,
+        coinTokenProgram: TOKEN_PROGRAM_ID,
+        // optional pc bank, the pc leg stays in the user wallet
+        pcBank: null,
+        pcBankLiquidityVault: null,
+        pcBankLiquidityVaultAuthority: null,
+        pcMint: null,
+        pcTokenProgram: null,
       })
+      .remainingAccounts(
+        await getHealthRemainingAccounts(
+          program,
+          userAOrbitlenAccount,
+          [RayBank, LpBank]
+        )
+      )
       .preInstructions([
         ComputeBudgetProgram.setComputeUnitLimit({ units: 1400000 }),
       ])
@@ -426,7 +461,9 @@ describe("raydium", () => {
 
     let tx = await program.methods
       .raydiumWithdraw(
-        new BN(5 * LAMPORTS_PER_TOKEN) // lp amount
+        new BN(5 * LAMPORTS_PER_TOKEN), // lp amount
+        new BN(0), // minCoinAmount
+        new BN(0) // minPcAmount
       )
       .accounts({
         // amm accounts
@@ -457,10 +494,28 @@ describe("raydium", () => {
         orbitlenAccount: userAOrbitlenAccount,
         bank: RayBank, // coin bank
         bankLiquidityVault: RayLiquidityVault,
+        lpBank: LpBank,
+        lpBankLiquidityVault: LpLiquidityVault,
+        bankLiquidityVaultAuthority: RayLiquidityVaultAuthority,
+        lpBankLiquidityVaultAuthority: LpLiquidityVaultAuthority,
         userCoinTokenAccount: userARay,
         coinMint: globalInfo.coinMint,
         tokenProgram: TOKEN_PROGRAM_ID,
+        coinTokenProgram: TOKEN_PROGRAM_ID,
+        // optional pc bank, the pc leg stays in the user wallet
+        pcBank: null,
+        pcBankLiquidityVault: null,
+        pcBankLiquidityVaultAuthority: null,
+        pcMint: null,
+        pcTokenProgram: null,
       })
+      .remainingAccounts(
+        await getHealthRemainingAccounts(
+          program,
+          userAOrbitlenAccount,
+          [RayBank]
+        )
+      )
       .preInstructions([
         ComputeBudgetProgram.setComputeUnitLimit({ units: 1600000 }),
       ])
@@ -522,7 +577,22 @@ describe("raydium", () => {
         userCoinTokenAccount: userARay,
         coinMint: globalInfo.coinMint,
         tokenProgram: TOKEN_PROGRAM_ID,
+        coinTokenProgram: TOKEN_PROGRAM_ID,
+        // optional pc bank, the pc leg stays in the user wallet
+        pcBank: null,
+        pcBankLiquidityVault: null,
+        pcBankLiquidityVaultAuthority: null,
+        pcMint: null,
+        pcTokenProgram: null,
+        userPcTokenAccount: null,
       })
+      .remainingAccounts(
+        await getHealthRemainingAccounts(
+          program,
+          userAOrbitlenAccount,
+          [RayBank]
+        )
+      )
       .preInstructions([
         ComputeBudgetProgram.setComputeUnitLimit({ units: 1600000 }),
       ])
@@ -586,7 +656,22 @@ describe("raydium", () => {
         userCoinTokenAccount: userARay,
         coinMint: globalInfo.coinMint,
         tokenProgram: TOKEN_PROGRAM_ID,
+        coinTokenProgram: TOKEN_PROGRAM_ID,
+        // optional pc bank, the pc leg stays in the user wallet
+        pcBank: null,
+        pcBankLiquidityVault: null,
+        pcBankLiquidityVaultAuthority: null,
+        pcMint: null,
+        pcTokenProgram: null,
+        userPcTokenAccount: null,
       })
+      .remainingAccounts(
+        await getHealthRemainingAccounts(
+          program,
+          userAOrbitlenAccount,
+          [RayBank]
+        )
+      )
       .preInstructions([
         ComputeBudgetProgram.setComputeUnitLimit({ units: 1600000 }),
       ])
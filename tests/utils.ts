@@ -3,7 +3,10 @@ import {
   LAMPORTS_PER_SOL,
   Connection,
   Signer,
+  AccountMeta,
 } from "@solana/web3.js";
+import { Program } from "@coral-xyz/anchor";
+import { OrbitLen } from "../target/types/orbit_len";
 
 export function delay(ms: number) {
   return new Promise((resolve) => setTimeout(resolve, ms));
@@ -27,3 +30,85 @@ export async function safeAirdrop(address: PublicKey, connection: Connection) {
     console.log(`Airdropped ${(newBalance - balance) / LAMPORTS_PER_SOL} SOL`);
   }
 }
+
+/**
+ * Health check remaining accounts of an Orbitlen account: the e-mode category account when
+ * the account opted into one, then each active balance's bank followed by the oracle
+ * accounts `fetch_bank_price` expects for it. `extraBanks` are banks the instruction opens
+ * a balance in.
+ */
+export async function getHealthRemainingAccounts(
+  program: Program<OrbitLen>,
+  orbitlenAccount: PublicKey,
+  extraBanks: PublicKey[] = []
+): Promise<AccountMeta[]> {
+  const account = await program.account.orbitlenAccount.fetch(orbitlenAccount);
+  const banks = account.lendingAccount.balances
+    .map((balance) => balance.bankPk)
+    .filter((bankPk) => !bankPk.equals(PublicKey.default));
+
+  for (const bank of extraBanks) {
+    if (!banks.some((bankPk) => bankPk.equals(bank))) {
+      banks.push(bank);
+    }
+  }
+
+  const remainingAccounts: AccountMeta[] = [];
+  if (account.emodeCategory !== 0) {
+    const [emodeCategory] = PublicKey.findProgramAddressSync(
+      [Buffer.from("emode_category"), Buffer.from([account.emodeCategory])],
+      program.programId
+    );
+    remainingAccounts.push({ pubkey: emodeCategory, isSigner: false, isWritable: false });
+  }
+  for (const bankPk of banks) {
+    const bank = await program.account.bank.fetch(bankPk);
+    // Raydium LP banks are priced from the pool invariant and both tokens' feeds
+    let oracles: PublicKey[];
+    if (bank.config.bankType === 1) {
+      const lpConfig = bank.config.raydiumLpConfig;
+      oracles = [
+        lpConfig.coinFeedDataKey,
+        lpConfig.pcFeedDataKey,
+        lpConfig.amm,
+        lpConfig.ammOpenOrders,
+        lpConfig.ammCoinVault,
+        lpConfig.ammPcVault,
+        bank.mint,
+      ];
+    } else if (bank.config.bankType === 2) {
+      const lpConfig = bank.config.raydiumCpmmLpConfig;
+      oracles = [
+        lpConfig.token0FeedDataKey,
+        lpConfig.token1FeedDataKey,
+        lpConfig.poolState,
+        lpConfig.token0Vault,
+        lpConfig.token1Vault,
+      ];
+    } else {
+      oracles = [bank.config.feedDataKey];
+    }
+
+    for (const pubkey of [bankPk, ...oracles]) {
+      remainingAccounts.push({ pubkey, isSigner: false, isWritable: false });
+    }
+  }
+
+  return remainingAccounts;
+}
+
+/**
+ * Health report of an Orbitlen account, simulated through `lending_account_get_health`.
+ */
+export async function getAccountHealth(
+  program: Program<OrbitLen>,
+  orbitlenAccount: PublicKey
+) {
+  return await program.methods
+    .lendingAccountGetHealth()
+    .accounts({ orbitlenAccount })
+    .remainingAccounts(
+      await getHealthRemainingAccounts(program, orbitlenAccount)
+    )
+    .view();
+}